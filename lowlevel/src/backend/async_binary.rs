@@ -0,0 +1,59 @@
+use std::process::Stdio;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+};
+
+use super::is_complete_response;
+
+/// The asynchronous counterpart of `BinaryBackend`, running the solver as a
+/// [`tokio`] child process.
+pub(crate) struct AsyncBinaryBackend {
+    /// Only kept such that the solver is killed once the backend is dropped
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    buf: String,
+}
+
+impl AsyncBinaryBackend {
+    /// Spawns the solver, which must happen within a [`tokio`] runtime.
+    pub(crate) fn new(
+        program: impl AsRef<std::ffi::OsStr>,
+        init: impl FnOnce(&mut Command),
+    ) -> Result<Self, std::io::Error> {
+        let mut cmd = Command::new(program);
+        init(&mut cmd);
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        Ok(AsyncBinaryBackend {
+            _child: child,
+            stdin,
+            stdout,
+            buf: String::new(),
+        })
+    }
+    pub(crate) async fn exec(&mut self, cmd: &crate::Command) -> Result<&str, crate::Error> {
+        self.stdin.write_all(format!("{cmd}\n").as_bytes()).await?;
+        self.stdin.flush().await?;
+
+        self.buf.clear();
+        loop {
+            let n = self.stdout.read_line(&mut self.buf).await?;
+            if n == 0 {
+                // The solver exited, for example by being killed
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            if is_complete_response(&self.buf) {
+                return Ok(&self.buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use super::Backend;
+
+/// A backend which delegates to a callback, called with the text of each
+/// command and returning the text of the response of the solver.
+///
+/// This bridges to solvers which are not reachable through a process or a
+/// socket, such as a solver compiled to WebAssembly running in a web worker.
+///
+/// The callback must block until the solver has responded. Solvers which can
+/// only be awaited can instead implement `AsyncBackend`, which is enabled by
+/// the `async` feature and does not depend on any particular runtime.
+///
+/// ```
+/// # use smtlib_lowlevel::{ast::{CheckSatResponse, Command, GeneralResponse, SpecificSuccessResponse}, backend::CallbackBackend, Driver};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let backend = CallbackBackend::new(|cmd: &str| match cmd {
+///     "(check-sat)" => "sat".to_string(),
+///     _ => "success".to_string(),
+/// });
+/// let mut driver = Driver::new(backend, false)?;
+/// assert_eq!(
+///     driver.exec(&Command::CheckSat)?,
+///     GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::CheckSatResponse(
+///         CheckSatResponse::Sat
+///     )),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct CallbackBackend<F> {
+    callback: F,
+}
+
+impl<F> CallbackBackend<F>
+where
+    F: FnMut(&str) -> String,
+{
+    /// Responds to each command with the result of calling `callback` with
+    /// the command formatted as SMT-LIB.
+    pub fn new(callback: F) -> Self {
+        CallbackBackend { callback }
+    }
+}
+
+impl<F> Backend for CallbackBackend<F>
+where
+    F: FnMut(&str) -> String,
+{
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        Ok((self.callback)(&cmd.to_string()))
+    }
+}
@@ -1,6 +1,6 @@
 use std::ffi::OsStr;
 
-use super::{Backend, BinaryBackend};
+use super::{Backend, BinaryBackend, KillHandle};
 
 pub struct Cvc5Binary {
     bin: BinaryBackend,
@@ -22,4 +22,40 @@ impl Backend for Cvc5Binary {
     fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
         self.bin.exec(cmd).map(Into::into)
     }
+    fn exec_batch(&mut self, cmds: &[crate::Command]) -> Result<Vec<String>, crate::Error> {
+        self.bin.exec_batch(cmds)
+    }
+    fn kill_handle(&self) -> Option<KillHandle> {
+        Some(self.bin.kill_handle())
+    }
+}
+
+/// The asynchronous counterpart of [`Cvc5Binary`], running `cvc5` as a
+/// [`tokio`] child process.
+#[cfg(feature = "async-cvc5")]
+pub struct Cvc5BinaryAsync {
+    bin: super::async_binary::AsyncBinaryBackend,
+}
+
+#[cfg(feature = "async-cvc5")]
+impl Cvc5BinaryAsync {
+    /// Spawns `cvc5`, which must happen within a [`tokio`] runtime. The
+    /// process is killed once the backend is dropped.
+    pub fn new(cvc5: impl AsRef<OsStr>) -> Result<Self, std::io::Error> {
+        Ok(Cvc5BinaryAsync {
+            bin: super::async_binary::AsyncBinaryBackend::new(cvc5, |cmd| {
+                cmd.args(["--lang", "smt2"])
+                    .args(["--produce-models"])
+                    .args(["--incremental"]);
+            })?,
+        })
+    }
+}
+
+#[cfg(feature = "async-cvc5")]
+#[async_trait::async_trait(?Send)]
+impl super::AsyncBackend for Cvc5BinaryAsync {
+    async fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        self.bin.exec(cmd).await.map(Into::into)
+    }
 }
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use super::Backend;
+
+type Respond = Box<dyn FnMut(&crate::Command) -> Result<String, crate::Error> + Send>;
+
+/// A backend which does not communicate with any solver, but instead responds
+/// to commands as instructed, for testing code built upon `smtlib` without
+/// having a solver installed.
+///
+/// ```
+/// # use smtlib_lowlevel::{ast::{CheckSatResponse, Command, GeneralResponse, SpecificSuccessResponse}, backend::MockBackend, Driver};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut driver = Driver::new(MockBackend::scripted(["sat"]), false)?;
+/// assert_eq!(
+///     driver.exec(&Command::CheckSat)?,
+///     GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::CheckSatResponse(
+///         CheckSatResponse::Sat
+///     )),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockBackend {
+    respond: Respond,
+}
+
+impl MockBackend {
+    /// Responds to each command with the result of calling `respond` with it.
+    pub fn new(mut respond: impl FnMut(&crate::Command) -> String + Send + 'static) -> Self {
+        MockBackend {
+            respond: Box::new(move |cmd| Ok(respond(cmd))),
+        }
+    }
+    /// Responds to the commands with a specific response, such as
+    /// `check-sat` and `get-model`, with each of `responses` in order, and to
+    /// all other commands with `success`.
+    ///
+    /// Once all of `responses` are used, executing a command with a specific
+    /// response returns an error, as if the solver had exited.
+    pub fn scripted(responses: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut responses: VecDeque<String> = responses.into_iter().map(Into::into).collect();
+        MockBackend {
+            respond: Box::new(move |cmd| {
+                if !cmd.has_response() {
+                    return Ok("success".to_string());
+                }
+                responses.pop_front().ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        format!("no scripted response left for {cmd}"),
+                    )
+                    .into()
+                })
+            }),
+        }
+    }
+}
+
+impl Backend for MockBackend {
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        (self.respond)(cmd)
+    }
+}
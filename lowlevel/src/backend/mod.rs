@@ -9,16 +9,39 @@
 //!
 //! - **[`Z3Binary`]**: A [Z3](https://github.com/Z3Prover/z3) backend using the binary CLI interface.
 //!     - **Enabled by feature:** `z3`
+//!     - The asynchronous `Z3BinaryAsync` is **enabled by feature:** `async-z3`
 //! - **[`Z3Static`]**: A [Z3](https://github.com/Z3Prover/z3) backend using the [`z3-sys` crate](https://github.com/prove-rs/z3.rs).
 //!     - **Enabled by feature:** `z3-static`
 //! - **[`Cvc5Binary`]**: A [cvc5](https://cvc5.github.io/) backend using the binary CLI interface.
 //!     - **Enabled by feature:** `cvc5`
+//!     - The asynchronous `Cvc5BinaryAsync` is **enabled by feature:** `async-cvc5`
+//! - **[`SocketBackend`]**: Any solver speaking SMT-LIB2 over a socket, such as a [`TcpStream`](std::net::TcpStream) to a solver running as a server.
+//!     - **Enabled by feature:** `socket`
+//!     - The asynchronous `AsyncSocketBackend` is **enabled by feature:** `async-socket`
+//! - **[`MockBackend`]**: Responds to commands as scripted, without running any solver, for testing.
+//!     - **Always enabled**
+//! - **[`CallbackBackend`]**: Delegates each command to a callback, for bridging to solvers running elsewhere, such as in a web worker.
+//!     - **Always enabled**
+//!
+//! Additionally, **[`RecordingBackend`]** wraps any other backend, recording
+//! the commands sent to it and the responses received.
 
 use std::{
     io::{BufRead, BufReader, Write},
-    process::{Child, ChildStdin, ChildStdout},
+    process::{Child, ChildStdin, ChildStdout, ExitStatus},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
+mod mock;
+pub use mock::*;
+
+mod recording;
+pub use recording::*;
+
+mod callback;
+pub use callback::*;
+
 #[cfg(feature = "cvc5")]
 mod cvc5;
 #[cfg(feature = "cvc5")]
@@ -26,22 +49,107 @@ pub use cvc5::*;
 
 #[cfg(feature = "z3")]
 mod z3_binary;
-use logos::Lexer;
 #[cfg(feature = "z3")]
 pub use z3_binary::*;
 
+#[cfg(any(feature = "async-z3", feature = "async-cvc5"))]
+mod async_binary;
+
+#[cfg(feature = "socket")]
+mod socket;
+#[cfg(feature = "socket")]
+pub use socket::*;
+
 #[cfg(feature = "z3-static")]
 mod z3_static;
 #[cfg(feature = "z3-static")]
 pub use z3_static::*;
 
-use crate::parse::Token;
-
 /// The [`Backend`] trait is used to interact with SMT solver using the SMT-LIB language.
 ///
 /// For more details read the [`backend`](crate::backend) module documentation.
 pub trait Backend {
     fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error>;
+    /// Executes all of `cmds` in order, returning the response to each of
+    /// them.
+    ///
+    /// The default implementation calls [`Backend::exec`] for each command,
+    /// but backends may override it to avoid a round-trip per command, for
+    /// example by writing all of them at once.
+    fn exec_batch(&mut self, cmds: &[crate::Command]) -> Result<Vec<String>, crate::Error> {
+        cmds.iter().map(|cmd| self.exec(cmd)).collect()
+    }
+    /// Returns a handle which can terminate the solver from another thread, for
+    /// example while [`Backend::exec`] is blocked waiting for a response.
+    ///
+    /// Returns `None` for backends which cannot be terminated, such as those
+    /// running in-process.
+    fn kill_handle(&self) -> Option<KillHandle> {
+        None
+    }
+}
+
+impl<B: Backend + ?Sized> Backend for Box<B> {
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        (**self).exec(cmd)
+    }
+    fn exec_batch(&mut self, cmds: &[crate::Command]) -> Result<Vec<String>, crate::Error> {
+        (**self).exec_batch(cmds)
+    }
+    fn kill_handle(&self) -> Option<KillHandle> {
+        (**self).kill_handle()
+    }
+}
+
+/// A handle to the process of a binary backend, obtained through
+/// [`Backend::kill_handle`].
+#[derive(Debug, Clone)]
+pub struct KillHandle(Arc<Mutex<Child>>);
+
+impl KillHandle {
+    /// Kills the solver process and waits for it to exit. Any pending or
+    /// future call to [`Backend::exec`] on the backend will return an error.
+    ///
+    /// Killing a process which has already exited is not an error.
+    pub fn kill(&self) -> Result<(), std::io::Error> {
+        let mut child = self.0.lock().unwrap();
+        if child.try_wait()?.is_none() {
+            child.kill()?;
+        }
+        child.wait()?;
+        Ok(())
+    }
+    /// Sends `SIGINT` to the solver process, which solvers such as Z3 handle
+    /// by aborting the command currently executing, answering `unknown` to a
+    /// `check-sat`, rather than exiting. Solvers which do not handle it are
+    /// terminated.
+    ///
+    /// Interrupting a process which has already exited is not an error. On
+    /// platforms without signals an error of kind
+    /// [`Unsupported`](std::io::ErrorKind::Unsupported) is returned.
+    pub fn interrupt(&self) -> Result<(), std::io::Error> {
+        // NOTE: The lock is held while signalling, such that the process
+        // cannot be reaped, and its pid reused, in the meantime
+        let mut child = self.0.lock().unwrap();
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        {
+            let pid = child.id() as libc::pid_t;
+            if unsafe { libc::kill(pid, libc::SIGINT) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+    /// Returns the exit status of the solver process if it has exited, and
+    /// `None` if it is still running. This does not block.
+    pub fn try_wait(&self) -> Result<Option<ExitStatus>, std::io::Error> {
+        self.0.lock().unwrap().try_wait()
+    }
 }
 
 #[cfg(feature = "async")]
@@ -54,9 +162,9 @@ pub trait AsyncBackend {
 }
 
 struct BinaryBackend {
-    #[allow(unused)]
-    child: Child,
-    stdin: ChildStdin,
+    child: Arc<Mutex<Child>>,
+    /// Only `None` while the backend is being dropped
+    stdin: Option<ChildStdin>,
     stdout: BufReader<ChildStdout>,
     buf: String,
 }
@@ -75,32 +183,135 @@ impl BinaryBackend {
         let stdout = BufReader::new(child.stdout.take().unwrap());
 
         Ok(BinaryBackend {
-            child,
-            stdin,
+            child: Arc::new(Mutex::new(child)),
+            stdin: Some(stdin),
             stdout,
             buf: String::new(),
         })
     }
     pub(crate) fn exec(&mut self, cmd: &crate::Command) -> Result<&str, crate::Error> {
         // println!("> {cmd}");
-        writeln!(self.stdin, "{cmd}")?;
-        self.stdin.flush()?;
+        let stdin = self.stdin.as_mut().unwrap();
+        writeln!(stdin, "{cmd}")?;
+        stdin.flush()?;
+
+        self.read_response()
+    }
+    /// Writes the commands in chunks of `BATCH_SIZE`, and reads back the
+    /// responses of each chunk before writing the next.
+    ///
+    /// Bounding the size of a chunk keeps a solver blocked on writing
+    /// responses to a full stdout from deadlocking with us blocked on writing
+    /// commands to its full stdin.
+    pub(crate) fn exec_batch(
+        &mut self,
+        cmds: &[crate::Command],
+    ) -> Result<Vec<String>, crate::Error> {
+        const BATCH_SIZE: usize = 256;
 
+        let mut responses = Vec::with_capacity(cmds.len());
+        let mut src = String::new();
+        for chunk in cmds.chunks(BATCH_SIZE) {
+            src.clear();
+            for cmd in chunk {
+                src.push_str(&cmd.to_string());
+                src.push('\n');
+            }
+            let stdin = self.stdin.as_mut().unwrap();
+            stdin.write_all(src.as_bytes())?;
+            stdin.flush()?;
+
+            for _ in chunk {
+                responses.push(self.read_response()?.to_string());
+            }
+        }
+        Ok(responses)
+    }
+    /// Reads the response to a single command
+    fn read_response(&mut self) -> Result<&str, crate::Error> {
         self.buf.clear();
         loop {
             let n = self.stdout.read_line(&mut self.buf)?;
             if n == 0 {
-                continue;
+                // The solver exited, for example by being killed
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
             }
-            if Lexer::new(self.buf.as_str()).fold(0i32, |acc, tok| match tok {
-                Token::LParen => acc + 1,
-                Token::RParen => acc - 1,
-                _ => acc,
-            }) != 0
-            {
+            if !is_complete_response(&self.buf) {
                 continue;
             }
             return Ok(&self.buf);
         }
     }
+    pub(crate) fn kill_handle(&self) -> KillHandle {
+        KillHandle(Arc::clone(&self.child))
+    }
+}
+
+impl Drop for BinaryBackend {
+    /// Asks the solver to `(exit)` and closes its stdin, giving it
+    /// `EXIT_TIMEOUT` to exit on its own before it is killed. Either way the
+    /// process is waited on, so that it does not linger as a zombie.
+    fn drop(&mut self) {
+        const EXIT_TIMEOUT: Duration = Duration::from_millis(100);
+
+        if let Some(mut stdin) = self.stdin.take() {
+            // NOTE: Errors here only indicate that the process already exited
+            let _ = writeln!(stdin, "(exit)").and_then(|()| stdin.flush());
+        }
+
+        let mut child = self.child.lock().unwrap();
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+        while Instant::now() < deadline {
+            match child.try_wait() {
+                Ok(None) => std::thread::sleep(Duration::from_millis(1)),
+                Ok(Some(_)) | Err(_) => return,
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Determines if `src` contains a complete response, that is, if all
+/// parentheses are balanced and no string literal or quoted symbol is left
+/// open.
+///
+/// Parentheses inside of string literals (`"..."`, where `""` is an escaped
+/// quote), quoted symbols (`|...|`), and comments are not counted, since the
+/// solvers happily echo back strings such as `"))"`.
+pub(crate) fn is_complete_response(src: &str) -> bool {
+    enum State {
+        Normal,
+        String,
+        QuotedSymbol,
+        Comment,
+    }
+
+    let mut state = State::Normal;
+    let mut depth = 0i32;
+
+    for c in src.chars() {
+        state = match (state, c) {
+            // NOTE: An escaped quote `""` leaves and immediately reenters the
+            // string, so it needs no special treatment.
+            (State::String, '"') => State::Normal,
+            (State::QuotedSymbol, '|') => State::Normal,
+            (State::Comment, '\n') => State::Normal,
+            (state @ (State::String | State::QuotedSymbol | State::Comment), _) => state,
+            (State::Normal, '"') => State::String,
+            (State::Normal, '|') => State::QuotedSymbol,
+            (State::Normal, ';') => State::Comment,
+            (State::Normal, '(') => {
+                depth += 1;
+                State::Normal
+            }
+            (State::Normal, ')') => {
+                depth -= 1;
+                State::Normal
+            }
+            (State::Normal, _) => State::Normal,
+        };
+    }
+
+    depth == 0 && matches!(state, State::Normal | State::Comment)
 }
@@ -0,0 +1,68 @@
+use super::{Backend, KillHandle};
+
+/// A backend which wraps another backend `B`, recording every command sent to
+/// it along with the raw response it gave.
+///
+/// The recorded commands can be inspected through
+/// [`Driver::backend`](crate::Driver::backend).
+///
+/// ```
+/// # use smtlib_lowlevel::{ast::Command, backend::{MockBackend, RecordingBackend}, Driver};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let backend = RecordingBackend::new(MockBackend::scripted(["sat"]));
+/// let mut driver = Driver::new(backend, false)?;
+/// driver.exec(&Command::CheckSat)?;
+/// let (cmd, res) = driver.backend().records().last().unwrap();
+/// assert_eq!((cmd, res.as_str()), (&Command::CheckSat, "sat"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingBackend<B> {
+    inner: B,
+    records: Vec<(crate::Command, String)>,
+}
+
+impl<B> RecordingBackend<B> {
+    /// Wraps `inner`, recording every command executed from now on.
+    pub fn new(inner: B) -> Self {
+        RecordingBackend {
+            inner,
+            records: Vec::new(),
+        }
+    }
+    /// Every command executed so far, in order, paired with the raw response
+    /// to it.
+    pub fn records(&self) -> &[(crate::Command, String)] {
+        &self.records
+    }
+    /// Removes all of the recorded commands.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+    /// The wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    /// Unwraps the backend, discarding the recorded commands.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Backend> Backend for RecordingBackend<B> {
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        let res = self.inner.exec(cmd)?;
+        self.records.push((cmd.clone(), res.clone()));
+        Ok(res)
+    }
+    fn exec_batch(&mut self, cmds: &[crate::Command]) -> Result<Vec<String>, crate::Error> {
+        let responses = self.inner.exec_batch(cmds)?;
+        self.records
+            .extend(cmds.iter().cloned().zip(responses.iter().cloned()));
+        Ok(responses)
+    }
+    fn kill_handle(&self) -> Option<KillHandle> {
+        self.inner.kill_handle()
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use super::{is_complete_response, Backend};
+
+/// A backend communicating with a solver over a stream, such as a
+/// [`TcpStream`] connected to a solver running as a server.
+///
+/// Commands are written to the stream one per line, and the response is read
+/// until all parentheses are balanced. The solver is expected to speak plain
+/// SMT-LIB2, exactly as it would over standard in- and output.
+pub struct SocketBackend<S: Read + Write = TcpStream> {
+    stream: BufReader<S>,
+    buf: String,
+}
+
+impl SocketBackend<TcpStream> {
+    /// Connects to the solver listening at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, std::io::Error> {
+        Ok(Self::new(TcpStream::connect(addr)?))
+    }
+}
+
+impl<S: Read + Write> SocketBackend<S> {
+    /// Communicate with a solver over an already established `stream`.
+    pub fn new(stream: S) -> Self {
+        SocketBackend {
+            stream: BufReader::new(stream),
+            buf: String::new(),
+        }
+    }
+}
+
+impl<S: Read + Write> Backend for SocketBackend<S> {
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        let stream = self.stream.get_mut();
+        writeln!(stream, "{cmd}")?;
+        stream.flush()?;
+
+        self.buf.clear();
+        loop {
+            let n = self.stream.read_line(&mut self.buf)?;
+            if n == 0 {
+                // The server closed the connection
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            if is_complete_response(&self.buf) {
+                return Ok(self.buf.clone());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async-socket")]
+pub use tokio_socket::*;
+
+#[cfg(feature = "async-socket")]
+mod tokio_socket {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+        net::{TcpStream, ToSocketAddrs},
+    };
+
+    use crate::backend::{is_complete_response, AsyncBackend};
+
+    /// The asynchronous counterpart of
+    /// [`SocketBackend`](super::SocketBackend), communicating over a
+    /// [`tokio`] stream.
+    pub struct AsyncSocketBackend<S: AsyncRead + AsyncWrite + Unpin = TcpStream> {
+        stream: BufReader<S>,
+        buf: String,
+    }
+
+    impl AsyncSocketBackend<TcpStream> {
+        /// Connects to the solver listening at `addr`.
+        pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self, std::io::Error> {
+            Ok(Self::new(TcpStream::connect(addr).await?))
+        }
+    }
+
+    impl<S: AsyncRead + AsyncWrite + Unpin> AsyncSocketBackend<S> {
+        /// Communicate with a solver over an already established `stream`.
+        pub fn new(stream: S) -> Self {
+            AsyncSocketBackend {
+                stream: BufReader::new(stream),
+                buf: String::new(),
+            }
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl<S: AsyncRead + AsyncWrite + Unpin> AsyncBackend for AsyncSocketBackend<S> {
+        async fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+            let stream = self.stream.get_mut();
+            stream.write_all(format!("{cmd}\n").as_bytes()).await?;
+            stream.flush().await?;
+
+            self.buf.clear();
+            loop {
+                let n = self.stream.read_line(&mut self.buf).await?;
+                if n == 0 {
+                    // The server closed the connection
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
+                if is_complete_response(&self.buf) {
+                    return Ok(self.buf.clone());
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::ffi::OsStr;
 
-use super::{Backend, BinaryBackend};
+use super::{Backend, BinaryBackend, KillHandle};
 
 pub struct Z3Binary {
     bin: BinaryBackend,
@@ -20,4 +20,38 @@ impl Backend for Z3Binary {
     fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
         self.bin.exec(cmd).map(Into::into)
     }
+    fn exec_batch(&mut self, cmds: &[crate::Command]) -> Result<Vec<String>, crate::Error> {
+        self.bin.exec_batch(cmds)
+    }
+    fn kill_handle(&self) -> Option<KillHandle> {
+        Some(self.bin.kill_handle())
+    }
+}
+
+/// The asynchronous counterpart of [`Z3Binary`], running `z3` as a [`tokio`]
+/// child process.
+#[cfg(feature = "async-z3")]
+pub struct Z3BinaryAsync {
+    bin: super::async_binary::AsyncBinaryBackend,
+}
+
+#[cfg(feature = "async-z3")]
+impl Z3BinaryAsync {
+    /// Spawns `z3`, which must happen within a [`tokio`] runtime. The process
+    /// is killed once the backend is dropped.
+    pub fn new(z3: impl AsRef<OsStr>) -> Result<Self, std::io::Error> {
+        Ok(Z3BinaryAsync {
+            bin: super::async_binary::AsyncBinaryBackend::new(z3, |cmd| {
+                cmd.arg("smtlib2_compliant=true").arg("-in");
+            })?,
+        })
+    }
+}
+
+#[cfg(feature = "async-z3")]
+#[async_trait::async_trait(?Send)]
+impl super::AsyncBackend for Z3BinaryAsync {
+    async fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        self.bin.exec(cmd).await.map(Into::into)
+    }
 }
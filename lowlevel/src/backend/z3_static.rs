@@ -2,19 +2,46 @@ use std::ffi::{CStr, CString};
 
 use crate::Backend;
 
+/// A Z3 backend running in-process through the `z3-sys` crate.
+///
+/// Commands are evaluated using `Z3_eval_smtlib2_string`, with
+/// `:smtlib2_compliant` set and terms printed in SMT-LIB2 compliant mode,
+/// such that responses, including models, parse like those of `Z3Binary`.
 pub struct Z3Static {
     ctx: z3_sys::Z3_context,
 }
 
 impl Z3Static {
+    /// Creates a context, with `timeout` in milliseconds if given. See
+    /// [`Z3Static::with_params`] for other parameters.
     pub fn new(timeout: &Option<u64>) -> Result<Self, std::io::Error> {
+        let timeout = timeout.map(|timeout| timeout.to_string());
+        let params: Vec<_> = timeout
+            .iter()
+            .map(|timeout| ("timeout", timeout.as_str()))
+            .collect();
+        Self::with_params(&params)
+    }
+    /// Creates a context configured with each of the `(name, value)` pairs of
+    /// `params`, such as `("smt.random_seed", "42")` or `("model.compact",
+    /// "false")`.
+    ///
+    /// Returns an error of kind
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) if a name or value
+    /// contains NUL. Note that Z3 only warns about unknown parameters.
+    pub fn with_params(params: &[(&str, &str)]) -> Result<Self, std::io::Error> {
+        let params = params
+            .iter()
+            .map(|&(name, value)| Ok((CString::new(name)?, CString::new(value)?)))
+            .collect::<Result<Vec<_>, std::ffi::NulError>>()?;
         Ok(Z3Static {
             ctx: unsafe {
                 let cfg = z3_sys::Z3_mk_config();
-                if let Some(timeout) = timeout {
-                    z3_sys::Z3_set_param_value(cfg, CString::new("timeout").unwrap().as_ptr(), CString::new(timeout.to_string()).unwrap().as_ptr());
+                for (name, value) in &params {
+                    z3_sys::Z3_set_param_value(cfg, name.as_ptr(), value.as_ptr());
                 }
                 let ctx = z3_sys::Z3_mk_context_rc(cfg);
+                z3_sys::Z3_del_config(cfg);
                 z3_sys::Z3_set_error_handler(ctx, None);
                 z3_sys::Z3_set_ast_print_mode(ctx, z3_sys::AstPrintMode::SmtLib2Compliant);
 
@@ -28,11 +55,30 @@ impl Z3Static {
     }
 }
 
+impl Z3Static {
+    /// Returns the error of the last call to the Z3 API, if any. As no error
+    /// handler is set, Z3 records the error instead of aborting, and resets
+    /// it on the next call.
+    fn last_error(&self) -> Result<(), crate::Error> {
+        let code = unsafe { z3_sys::Z3_get_error_code(self.ctx) };
+        if code == z3_sys::ErrorCode::Ok {
+            return Ok(());
+        }
+        let msg = unsafe { CStr::from_ptr(z3_sys::Z3_get_error_msg(self.ctx, code)) };
+        Err(crate::Error::Solver(msg.to_string_lossy().into_owned()))
+    }
+}
+
 impl Backend for Z3Static {
-    fn exec(&mut self, s: &crate::Command) -> Result<String, crate::Error> {
-        let s = CString::new(s.to_string()).unwrap();
-        let res = unsafe { z3_sys::Z3_eval_smtlib2_string(self.ctx, s.as_ptr()) };
-        let s = unsafe { CStr::from_ptr(res) }.to_str().unwrap().to_string();
-        Ok(s)
+    fn exec(&mut self, cmd: &crate::Command) -> Result<String, crate::Error> {
+        // NOTE: Commands containing NUL, such as in a string literal, cannot
+        // be passed to Z3 as a C string
+        let cmd = CString::new(cmd.to_string()).map_err(std::io::Error::from)?;
+        let res = unsafe { z3_sys::Z3_eval_smtlib2_string(self.ctx, cmd.as_ptr()) };
+        self.last_error()?;
+        let res = unsafe { CStr::from_ptr(res) }
+            .to_str()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(res.to_string())
     }
 }
@@ -1,6 +1,7 @@
+use logos::Logos;
+
 use crate::parse::{ParseError, Parser, Token};
-use regex::Regex;
-use num_bigint::BigUint;
+use num_bigint::{BigUint, ParseBigIntError};
 use num_traits::Num;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -38,6 +39,32 @@ impl SmtlibParse for Decimal {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Symbol(pub String);
+impl Symbol {
+    /// Construct the symbol named `name`, quoting it as `|name|` only if it
+    /// is not a valid simple symbol, for example if it contains whitespace,
+    /// starts with a digit, or is a reserved word.
+    ///
+    /// Returns `None` if `name` contains `|` or `\`, which cannot occur in
+    /// symbols, not even quoted ones.
+    pub fn quoted(name: &str) -> Option<Symbol> {
+        if name.contains(['|', '\\']) {
+            None
+        } else if Symbol::is_simple(name) {
+            Some(Symbol(name.to_string()))
+        } else {
+            Some(Symbol(format!("|{name}|")))
+        }
+    }
+    /// Determines if `name` is a simple symbol, that is, a symbol which can be
+    /// written without quoting it.
+    pub fn is_simple(name: &str) -> bool {
+        let mut lexer = Token::lexer(name);
+        !name.starts_with(|c: char| c.is_ascii_digit() || c == '|')
+            && lexer.next() == Some(Token::Symbol)
+            && lexer.slice() == name
+            && lexer.next().is_none()
+    }
+}
 impl std::fmt::Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -90,14 +117,11 @@ impl SmtlibParse for Fieldelement {
     }
 }
 impl Fieldelement {
-    pub fn parse(&self) -> Result<BigUint, std::num::ParseIntError> {
-        let re = Regex::new(r"f[0-9]+").unwrap();
-        let s = self.to_string();
-        let relevant_part = re.find(&s.as_str()).unwrap().as_str();
-        
-        // Convert to BigUint
-        let big_u = BigUint::from_str_radix(relevant_part, 10).expect("Invalid number");
-        Ok(big_u)
+    /// The value of the field element, that is, `N` in `#fNmM`
+    pub fn parse(&self) -> Result<BigUint, ParseBigIntError> {
+        let value = self.0.trim_start_matches("#f");
+        let value = value.split_once('m').map_or(value, |(value, _)| value);
+        BigUint::from_str_radix(value, 10)
     }
 }
 
@@ -155,6 +179,86 @@ impl SmtlibParse for Keyword {
     }
 }
 
+/// The largest code point of the characters of the theory of strings.
+const MAX_STRING_CHAR: u32 = 0x2FFFF;
+
+/// Constructs the string literal denoting `value` in the SMT-LIB 2.6 theory of
+/// strings, including the surrounding quotes. Quotes are escaped as `""`, and
+/// characters other than printable ASCII, as well as `\`, are escaped as
+/// `\u{...}`, such that `café` becomes `"caf\u{e9}"`.
+///
+/// Panics if `value` contains characters above `U+2FFFF`, which are not part
+/// of the alphabet of the theory. See [`try_escape_string`] for a checked
+/// version.
+pub fn escape_string(value: &str) -> String {
+    try_escape_string(value).unwrap_or_else(|| {
+        panic!("{value:?} contains characters which are not part of the theory of strings")
+    })
+}
+
+/// Constructs the string literal denoting `value` like [`escape_string`], or
+/// returns `None` if `value` contains characters above `U+2FFFF`.
+pub fn try_escape_string(value: &str) -> Option<String> {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\"\""),
+            ' '..='~' if c != '\\' => literal.push(c),
+            _ if c as u32 > MAX_STRING_CHAR => return None,
+            _ => literal.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        }
+    }
+    literal.push('"');
+    Some(literal)
+}
+
+/// The value denoted by the string `literal`, including the surrounding quotes,
+/// in the SMT-LIB 2.6 theory of strings. This is the inverse of
+/// [`escape_string`], decoding `""` as well as escape sequences of the forms
+/// `\ud₃d₂d₁d₀` and `\u{d₀}` through `\u{d₄d₃d₂d₁d₀}`.
+///
+/// Returns `None` if `literal` is not surrounded by quotes.
+pub fn unescape_string(literal: &str) -> Option<String> {
+    let inner = literal
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .replace("\"\"", "\"");
+    let mut value = String::with_capacity(inner.len());
+    let mut rest = inner.as_str();
+    while let Some(start) = rest.find("\\u") {
+        value.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let after = &rest[2..];
+        let (digits, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) if (1..=5).contains(&end) => (&braced[..end], end + 4),
+                _ => ("", 0),
+            },
+            None => (after.get(..4).unwrap_or(""), 6),
+        };
+        let c = Some(digits)
+            .filter(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .filter(|&c| c <= MAX_STRING_CHAR)
+            .and_then(char::from_u32);
+        match c {
+            Some(c) => {
+                value.push(c);
+                rest = &rest[len..];
+            }
+            // NOTE: Anything not forming a valid escape sequence denotes
+            // itself
+            None => {
+                value.push('\\');
+                rest = &rest[1..];
+            }
+        }
+    }
+    value.push_str(rest);
+    Some(value)
+}
+
 pub type BValue = bool;
 
 pub(crate) trait SmtlibParse: Sized {
@@ -172,11 +276,20 @@ impl SmtlibParse for String {
     }
 }
 impl SmtlibParse for bool {
-    fn is_start_of(_offset: usize, _tokens: &mut Parser) -> bool {
-        todo!()
+    fn is_start_of(offset: usize, tokens: &mut Parser) -> bool {
+        tokens.nth_matches(offset, Token::Symbol, "true")
+            || tokens.nth_matches(offset, Token::Symbol, "false")
     }
 
-    fn parse(_tokens: &mut Parser) -> Result<Self, ParseError> {
-        todo!()
+    fn parse(tokens: &mut Parser) -> Result<Self, ParseError> {
+        let value = if tokens.nth_matches(0, Token::Symbol, "true") {
+            true
+        } else if tokens.nth_matches(0, Token::Symbol, "false") {
+            false
+        } else {
+            return Err(tokens.stuck("bool"));
+        };
+        tokens.bump();
+        Ok(value)
     }
 }
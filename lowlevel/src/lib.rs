@@ -4,13 +4,18 @@
 //!
 //! _A low-level API for interacting with SMT solvers._
 
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Write};
 
-use ast::{QualIdentifier, Term};
+use itertools::Itertools;
+
+use ast::{Identifier, MatchCase, Pattern, QualIdentifier, SpecConstant, Term, VarBinding};
 #[cfg(feature = "async")]
 use backend::AsyncBackend;
 use backend::Backend;
-use parse::ParseError;
+use lexicon::{Numeral, Symbol};
+use num_bigint::BigInt;
+use num_traits::{Signed, Zero};
+pub use parse::{tokenize, ModelEntries, ParseError, SourceSpan, Token};
 
 use crate::ast::{Command, GeneralResponse};
 
@@ -32,12 +37,31 @@ pub enum Error {
     ),
     #[error(transparent)]
     IO(#[from] std::io::Error),
+    /// An error reported by a solver through its API, rather than as an
+    /// `(error ...)` response, such as by [`Z3Static`](backend::Z3Static).
+    #[error("the solver reported an error: {0}")]
+    Solver(String),
 }
 
-#[derive(Debug)]
+/// A callback invoked with every command sent to and every response received
+/// from a solver. See [`Driver::set_logger`].
+pub type Logger = Box<dyn FnMut(&str) + Send>;
+
 pub struct Driver<B> {
     backend: B,
     verbose: bool,
+    logger: Option<Logger>,
+    record_transcript: bool,
+    transcript: String,
+}
+
+impl<B: std::fmt::Debug> std::fmt::Debug for Driver<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Driver")
+            .field("backend", &self.backend)
+            .field("verbose", &self.verbose)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<B> Driver<B>
@@ -45,30 +69,111 @@ where
     B: Backend,
 {
     pub fn new(backend: B, verbose: bool) -> Result<Self, Error> {
-        let mut driver = Self { backend, verbose };
+        let mut driver = Self {
+            backend,
+            verbose,
+            logger: None,
+            record_transcript: false,
+            transcript: String::new(),
+        };
 
         driver.exec(&Command::SetOption(ast::Option::PrintSuccess(true)))?;
 
         Ok(driver)
     }
+    /// Installs `logger`, which is invoked with the text of every command
+    /// executed from now on, followed by the text of the response to it.
+    pub fn set_logger(&mut self, logger: impl FnMut(&str) + Send + 'static) {
+        self.logger = Some(Box::new(logger));
+    }
+    /// Whether commands and responses are printed as they are executed.
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+    /// The backend commands are executed by.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+    /// Sets whether executed commands are recorded in the
+    /// [transcript](Driver::transcript). This is disabled by default, as the
+    /// transcript grows with every command.
+    pub fn record_transcript(&mut self, enable: bool) {
+        self.record_transcript = enable;
+    }
+    /// Whether executed commands are recorded in the
+    /// [transcript](Driver::transcript).
+    pub fn records_transcript(&self) -> bool {
+        self.record_transcript
+    }
+    /// Every command executed by the driver while recording was enabled using
+    /// [`Driver::record_transcript`], one per line, forming a valid SMT-LIB2
+    /// script.
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+    /// Discards the commands recorded in the [transcript](Driver::transcript)
+    /// so far.
+    pub fn clear_transcript(&mut self) {
+        self.transcript.clear();
+    }
     pub fn exec(&mut self, cmd: &Command) -> Result<GeneralResponse, Error> {
         if self.verbose {
             println!("> {cmd}");
         }
+        if self.record_transcript {
+            writeln!(self.transcript, "{cmd}").unwrap();
+        }
+        if let Some(logger) = &mut self.logger {
+            logger(&cmd.to_string());
+        }
         let res = self.backend.exec(cmd)?;
-        let res = if let Some(res) = cmd.parse_response(&res)? {
-            GeneralResponse::SpecificSuccessResponse(res)
-        } else {
-            GeneralResponse::parse(&res)?
-        };
-        Ok(res)
+        if let Some(logger) = &mut self.logger {
+            logger(res.trim_end());
+        }
+        parse_response(cmd, &res)
+    }
+    /// Executes all of `cmds` in order using [`Backend::exec_batch`], returning
+    /// the response to each of them. Depending on the backend, this avoids a
+    /// round-trip to the solver per command.
+    ///
+    /// The logger is invoked with each command followed by its response once
+    /// all of the responses have been received.
+    pub fn exec_batch(&mut self, cmds: &[Command]) -> Result<Vec<GeneralResponse>, Error> {
+        for cmd in cmds {
+            if self.verbose {
+                println!("> {cmd}");
+            }
+            if self.record_transcript {
+                writeln!(self.transcript, "{cmd}").unwrap();
+            }
+        }
+        let responses = self.backend.exec_batch(cmds)?;
+        cmds.iter()
+            .zip(responses)
+            .map(|(cmd, res)| {
+                if let Some(logger) = &mut self.logger {
+                    logger(&cmd.to_string());
+                    logger(res.trim_end());
+                }
+                parse_response(cmd, &res)
+            })
+            .collect()
     }
 }
 
 #[cfg(feature = "async")]
-#[derive(Debug)]
 pub struct AsyncDriver<B> {
     backend: B,
+    logger: Option<Logger>,
+}
+
+#[cfg(feature = "async")]
+impl<B: std::fmt::Debug> std::fmt::Debug for AsyncDriver<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncDriver")
+            .field("backend", &self.backend)
+            .finish_non_exhaustive()
+    }
 }
 
 #[cfg(feature = "async")]
@@ -77,7 +182,10 @@ where
     B: AsyncBackend,
 {
     pub async fn new(backend: B) -> Result<Self, Error> {
-        let mut driver = Self { backend };
+        let mut driver = Self {
+            backend,
+            logger: None,
+        };
 
         driver
             .exec(&Command::SetOption(ast::Option::PrintSuccess(true)))
@@ -85,33 +193,319 @@ where
 
         Ok(driver)
     }
+    /// Installs `logger`, which is invoked with the text of every command
+    /// executed from now on, followed by the text of the response to it.
+    pub fn set_logger(&mut self, logger: impl FnMut(&str) + Send + 'static) {
+        self.logger = Some(Box::new(logger));
+    }
     pub async fn exec(&mut self, cmd: &Command) -> Result<GeneralResponse, Error> {
         // println!("> {cmd}");
+        if let Some(logger) = &mut self.logger {
+            logger(&cmd.to_string());
+        }
         let res = self.backend.exec(cmd).await?;
-        let res = if let Some(res) = cmd.parse_response(&res)? {
-            GeneralResponse::SpecificSuccessResponse(res)
-        } else {
-            GeneralResponse::parse(&res)?
-        };
-        Ok(res)
+        if let Some(logger) = &mut self.logger {
+            logger(res.trim_end());
+        }
+        parse_response(cmd, &res)
+    }
+}
+
+/// Parses `res` as the response to `cmd`. Since solvers may respond to any
+/// command with `success`, `unsupported`, or `(error ...)`, these are accepted
+/// even for commands with a specific response, such as `check-sat`.
+fn parse_response(cmd: &Command, res: &str) -> Result<GeneralResponse, Error> {
+    match cmd.parse_response(res) {
+        Ok(Some(res)) => Ok(GeneralResponse::SpecificSuccessResponse(res)),
+        Ok(None) => Ok(GeneralResponse::parse(res)?),
+        Err(e) => GeneralResponse::parse(res).map_err(|_| e.into()),
+    }
+}
+
+/// The width which [`Command::pretty`] tries to keep lines within.
+const PRETTY_WIDTH: usize = 80;
+
+/// An s-expression of the tokens of some SMT-LIB source.
+enum SExpr<'a> {
+    Atom(&'a str),
+    List(Vec<SExpr<'a>>),
+}
+
+impl<'a> SExpr<'a> {
+    /// Parses the s-expressions of `src`. Unbalanced parentheses are tolerated,
+    /// as the source is always produced by a `Display` implementation.
+    fn parse_all(src: &'a str) -> Vec<SExpr<'a>> {
+        let mut stack = vec![vec![]];
+        for (token, span) in tokenize(src) {
+            match token {
+                Token::LParen => stack.push(vec![]),
+                Token::RParen if stack.len() > 1 => {
+                    let list = stack.pop().unwrap();
+                    stack.last_mut().unwrap().push(SExpr::List(list));
+                }
+                _ => stack
+                    .last_mut()
+                    .unwrap()
+                    .push(SExpr::Atom(&src[span.offset()..span.end()])),
+            }
+        }
+        while stack.len() > 1 {
+            let list = stack.pop().unwrap();
+            stack.last_mut().unwrap().push(SExpr::List(list));
+        }
+        stack.pop().unwrap()
+    }
+    fn flat(&self) -> String {
+        match self {
+            SExpr::Atom(s) => s.to_string(),
+            SExpr::List(xs) => format!("({})", xs.iter().map(|x| x.flat()).format(" ")),
+        }
+    }
+    /// Writes the s-expression to `out`, which is currently at column
+    /// `column`. Lists not fitting on the rest of the line are broken up with
+    /// each argument on its own line, indented by `indent` from the list.
+    fn pretty(&self, out: &mut String, column: usize, indent: usize) {
+        let flat = self.flat();
+        match self {
+            SExpr::List(xs) if column + flat.len() > PRETTY_WIDTH && xs.len() > 1 => {
+                out.push('(');
+                xs[0].pretty(out, column + 1, indent);
+                for x in &xs[1..] {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(column + indent));
+                    x.pretty(out, column + indent, indent);
+                }
+                out.push(')');
+            }
+            _ => out.push_str(&flat),
+        }
+    }
+}
+
+impl Command {
+    /// Formats the command as SMT-LIB, like its `Display` implementation, but
+    /// spread over multiple lines when it is too long to fit within 80
+    /// characters. The arguments of an application which does not fit are
+    /// placed on separate lines, indented by `indent` spaces from the start of
+    /// the application.
+    ///
+    /// ```
+    /// # use smtlib_lowlevel::ast::Command;
+    /// let cmd = Command::parse("(assert (and (or a_long_name another_long_name) \
+    ///     (=> yet_another_long_name the_final_name)))").unwrap();
+    /// assert_eq!(cmd.pretty(2), "\
+    /// (assert
+    ///   (and
+    ///     (or a_long_name another_long_name)
+    ///     (=> yet_another_long_name the_final_name)))");
+    /// ```
+    pub fn pretty(&self, indent: usize) -> String {
+        let src = self.to_string();
+        let mut out = String::new();
+        for (i, sexpr) in SExpr::parse_all(&src).iter().enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            sexpr.pretty(&mut out, 0, indent);
+        }
+        out
+    }
+}
+
+/// The identifiers in `consts` except for those named by one of `bound`.
+fn free_consts<'a>(
+    consts: HashSet<&'a QualIdentifier>,
+    bound: &[&Symbol],
+) -> HashSet<&'a QualIdentifier> {
+    consts
+        .into_iter()
+        .filter(|q| match q {
+            QualIdentifier::Identifier(Identifier::Simple(sym))
+            | QualIdentifier::Sorted(Identifier::Simple(sym), _) => !bound.contains(&sym),
+            _ => true,
+        })
+        .collect()
+}
+
+/// The integer value of `t` if it is a numeral or a negated numeral.
+fn numeral_value(t: &Term) -> Option<BigInt> {
+    match t {
+        Term::SpecConstant(SpecConstant::Numeral(n)) => n.0.parse().ok(),
+        Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args)
+            if op.0 == "-" && args.len() == 1 =>
+        {
+            numeral_value(&args[0]).map(|n| -n)
+        }
+        _ => None,
+    }
+}
+
+/// The numeral with the value `n`, negated using `-` if `n` is negative.
+fn numeral_term(n: BigInt) -> Term {
+    let numeral = Term::SpecConstant(SpecConstant::Numeral(Numeral(n.magnitude().to_string())));
+    if n.is_negative() {
+        simple_application("-", vec![numeral])
+    } else {
+        numeral
+    }
+}
+
+/// The value of `t` if it is one of the literals `true` and `false`.
+fn bool_value(t: &Term) -> Option<bool> {
+    match t {
+        Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(s))) => match s.0.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn bool_term(b: bool) -> Term {
+    Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(Symbol(
+        b.to_string(),
+    ))))
+}
+
+fn simple_application(op: &str, args: Vec<Term>) -> Term {
+    Term::Application(
+        QualIdentifier::Identifier(Identifier::Simple(Symbol(op.to_string()))),
+        args,
+    )
+}
+
+/// The canonical form of the application of `op` to the already canonical
+/// `args`. See [`Term::canonicalize`].
+fn canonical_application(op: Symbol, args: Vec<Term>) -> Term {
+    let name = op.0.as_str();
+    let associative = matches!(name, "+" | "*" | "and" | "or");
+    let commutative = associative || matches!(name, "=" | "distinct");
+
+    let mut args = if associative {
+        args.into_iter()
+            .flat_map(|arg| match arg {
+                Term::Application(QualIdentifier::Identifier(Identifier::Simple(inner)), args)
+                    if inner == op =>
+                {
+                    args
+                }
+                arg => vec![arg],
+            })
+            .collect()
+    } else {
+        args
+    };
+
+    match (name, args.as_slice()) {
+        ("+" | "*", _) => {
+            let unit = BigInt::from(u8::from(name == "*"));
+            let mut constant = unit.clone();
+            args.retain(|arg| match numeral_value(arg) {
+                Some(n) if name == "+" => {
+                    constant += n;
+                    false
+                }
+                Some(n) => {
+                    constant *= n;
+                    false
+                }
+                None => true,
+            });
+            if name == "*" && constant.is_zero() {
+                return numeral_term(constant);
+            }
+            if constant != unit || args.is_empty() {
+                args.push(numeral_term(constant));
+            }
+        }
+        ("and" | "or", _) => {
+            let unit = name == "and";
+            if args.iter().any(|arg| bool_value(arg) == Some(!unit)) {
+                return bool_term(!unit);
+            }
+            args.retain(|arg| bool_value(arg) != Some(unit));
+            if args.is_empty() {
+                return bool_term(unit);
+            }
+        }
+        ("not", [arg]) => {
+            if let Some(b) = bool_value(arg) {
+                return bool_term(!b);
+            }
+            if let Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args) = arg
+            {
+                if op.0 == "not" && args.len() == 1 {
+                    return args[0].clone();
+                }
+            }
+        }
+        ("-", [arg]) => {
+            if let Some(n) = numeral_value(arg) {
+                return numeral_term(-n);
+            }
+        }
+        _ => {}
+    }
+
+    if commutative {
+        args.sort_by_cached_key(|arg| arg.to_string());
+    }
+    if matches!(name, "and" | "or") {
+        args.dedup();
     }
+    if associative && args.len() == 1 {
+        return args.pop().unwrap();
+    }
+    Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args)
 }
 
-// TODO: Use the definitions from 3.6.3 Scoping of variables and parameters
 impl Term {
+    /// All identifiers occurring free in the term, that is, not bound by a
+    /// `let`, quantifier, or `match` pattern, following the scoping rules of
+    /// section 3.6.3 of the SMT-LIB standard.
     pub fn all_consts(&self) -> HashSet<&QualIdentifier> {
+        self.free_identifiers(true)
+    }
+    /// Like [`Term::all_consts`], but without the identifiers heading an
+    /// application, such as `f` in `(f x)`, which are functions rather than
+    /// constants.
+    pub fn all_unapplied_consts(&self) -> HashSet<&QualIdentifier> {
+        self.free_identifiers(false)
+    }
+    fn free_identifiers(&self, heads: bool) -> HashSet<&QualIdentifier> {
         match self {
             Term::SpecConstant(_) => HashSet::new(),
             Term::Identifier(q) => std::iter::once(q).collect(),
-            Term::Application(q, args) => std::iter::once(q)
-                .chain(args.iter().flat_map(|arg| arg.all_consts()))
+            Term::Application(q, args) => heads
+                .then_some(q)
+                .into_iter()
+                .chain(args.iter().flat_map(|arg| arg.free_identifiers(heads)))
                 .collect(),
-            Term::Let(_, _) => todo!(),
-            // TODO
-            Term::Forall(_, _) => HashSet::new(),
-            Term::Exists(_, _) => todo!(),
-            Term::Match(_, _) => todo!(),
-            Term::Annotation(_, _) => todo!(),
+            Term::Let(bindings, body) => {
+                let bound: Vec<_> = bindings.iter().map(|b| &b.0).collect();
+                bindings
+                    .iter()
+                    .flat_map(|b| b.1.free_identifiers(heads))
+                    .chain(free_consts(body.free_identifiers(heads), &bound))
+                    .collect()
+            }
+            Term::Forall(vars, body) | Term::Exists(vars, body) => {
+                let bound: Vec<_> = vars.iter().map(|v| &v.0).collect();
+                free_consts(body.free_identifiers(heads), &bound)
+            }
+            Term::Match(t, cases) => t
+                .free_identifiers(heads)
+                .into_iter()
+                .chain(cases.iter().flat_map(|MatchCase(pattern, body)| {
+                    let bound: Vec<_> = match pattern {
+                        Pattern::Symbol(sym) => vec![sym],
+                        Pattern::Application(_, args) => args.iter().collect(),
+                    };
+                    free_consts(body.free_identifiers(heads), &bound)
+                }))
+                .collect(),
+            Term::Annotation(t, _) => t.free_identifiers(heads),
         }
     }
     pub fn strip_sort(self) -> Term {
@@ -127,11 +521,64 @@ impl Term {
                 QualIdentifier::Identifier(ident),
                 args.into_iter().map(|arg| arg.strip_sort()).collect(),
             ),
-            Term::Let(_, _) => todo!(),
-            Term::Forall(qs, rs) => Term::Forall(qs, rs),
-            Term::Exists(_, _) => todo!(),
-            Term::Match(_, _) => todo!(),
-            Term::Annotation(_, _) => todo!(),
+            Term::Let(bindings, body) => Term::Let(
+                bindings
+                    .into_iter()
+                    .map(|VarBinding(sym, t)| VarBinding(sym, t.strip_sort()))
+                    .collect(),
+                Box::new(body.strip_sort()),
+            ),
+            Term::Forall(qs, rs) => Term::Forall(qs, Box::new(rs.strip_sort())),
+            Term::Exists(qs, rs) => Term::Exists(qs, Box::new(rs.strip_sort())),
+            Term::Match(t, cases) => Term::Match(
+                Box::new(t.strip_sort()),
+                cases
+                    .into_iter()
+                    .map(|MatchCase(pattern, body)| MatchCase(pattern, body.strip_sort()))
+                    .collect(),
+            ),
+            Term::Annotation(t, attrs) => Term::Annotation(Box::new(t.strip_sort()), attrs),
+        }
+    }
+    /// Normalizes the term, such that terms which are equal up to the
+    /// following rewrites become structurally equal:
+    ///
+    /// - Nested applications of the associative `+`, `*`, `and`, and `or` are
+    ///   flattened, such that `(+ (+ a b) c)` becomes `(+ a b c)`.
+    /// - The arguments of the commutative `+`, `*`, `and`, `or`, `=`, and
+    ///   `distinct` are sorted.
+    /// - Numerals in `+` and `*`, and `true` and `false` in `and`, `or`, and
+    ///   `not` are folded, such that `(+ x 1 2)` becomes `(+ x 3)` and
+    ///   `(and x true)` becomes `x`.
+    ///
+    /// Only applications of simple identifiers are rewritten, leaving
+    /// applications with a sort ascription, such as `((as + Int) a b)`, as is.
+    pub fn canonicalize(self) -> Term {
+        match self {
+            Term::SpecConstant(_) | Term::Identifier(_) => self,
+            Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args) => {
+                canonical_application(op, args.into_iter().map(Term::canonicalize).collect())
+            }
+            Term::Application(q, args) => {
+                Term::Application(q, args.into_iter().map(Term::canonicalize).collect())
+            }
+            Term::Let(bindings, body) => Term::Let(
+                bindings
+                    .into_iter()
+                    .map(|VarBinding(sym, t)| VarBinding(sym, t.canonicalize()))
+                    .collect(),
+                Box::new(body.canonicalize()),
+            ),
+            Term::Forall(qs, rs) => Term::Forall(qs, Box::new(rs.canonicalize())),
+            Term::Exists(qs, rs) => Term::Exists(qs, Box::new(rs.canonicalize())),
+            Term::Match(t, cases) => Term::Match(
+                Box::new(t.canonicalize()),
+                cases
+                    .into_iter()
+                    .map(|MatchCase(pattern, body)| MatchCase(pattern, body.canonicalize()))
+                    .collect(),
+            ),
+            Term::Annotation(t, attrs) => Term::Annotation(Box::new(t.canonicalize()), attrs),
         }
     }
 }
@@ -1,11 +1,16 @@
+use std::io::BufRead;
+
 use itertools::Itertools;
 
-use crate::lexicon::SmtlibParse;
+use crate::{ast::ModelResponse, lexicon::SmtlibParse};
 
+/// The kinds of tokens of SMT-LIB, as produced by [`tokenize`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, logos::Logos)]
-pub(crate) enum Token {
+pub enum Token {
+    /// An opening parenthesis `(`.
     #[token("(")]
     LParen,
+    /// A closing parenthesis `)`.
     #[token(")")]
     RParen,
 
@@ -116,15 +121,34 @@ pub(crate) enum Token {
     #[regex(r":[~!@$%^&*_\-+=<>.?/a-zA-Z0-9]+")]
     Keyword,
 
-    #[error]
+    /// Any input which is not a valid token.
+    ///
     /// White Space Characters. A ⟨white_space_char⟩ is one of the following
     /// characters: 9dec (tab), 10dec (line feed), 13dec (carriage return), and
     /// 32dec (space).
     #[regex(r"[\t\n\r ]+", logos::skip)]
     #[regex(r";[^\n]*", logos::skip)]
+    #[error]
     Error,
 }
 
+/// Splits `src` into [`Token`]s with their spans, skipping white space and
+/// comments. Input which is not a valid token is produced as [`Token::Error`].
+///
+/// ```
+/// # use smtlib_lowlevel::{tokenize, Token};
+/// let tokens: Vec<_> = tokenize("(check-sat) ; done").collect();
+/// assert_eq!(tokens.len(), 3);
+/// assert_eq!(tokens[1].0, Token::Reserved);
+/// assert_eq!((tokens[1].1.offset(), tokens[1].1.len()), (1, 9));
+/// ```
+pub fn tokenize(src: &str) -> impl Iterator<Item = (Token, SourceSpan)> + '_ {
+    logos::Lexer::<Token>::new(src)
+        .spanned()
+        .map(|(t, r)| (t, SourceSpan::from((r.start, r.len()))))
+}
+
+/// A span of bytes in a source, given by its offset and length.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceSpan {
@@ -214,6 +238,9 @@ impl<T: std::hash::Hash> std::hash::Hash for Spanned<T> {
     }
 }
 
+/// An error produced when parsing SMT-LIB, pointing at the offending token
+/// of the source. Rendering it using [`miette`] shows the source with the
+/// offending token underlined.
 #[derive(Debug, thiserror::Error, miette::Diagnostic, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[error("{error}")]
@@ -228,6 +255,22 @@ pub struct ParseError {
     help: Option<String>,
 }
 
+impl ParseError {
+    /// The byte span of the offending token in the source. At the end of the
+    /// source, this is the empty span at its end.
+    pub fn span(&self) -> SourceSpan {
+        self.err_span
+    }
+    /// The source which was being parsed.
+    pub fn src(&self) -> &str {
+        &self.src
+    }
+    /// The text of the offending token.
+    pub fn token(&self) -> &str {
+        &self.src[self.err_span.offset()..self.err_span.end()]
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Parser<'src> {
     src: &'src str,
@@ -238,10 +281,7 @@ pub(crate) struct Parser<'src> {
 
 impl<'src> Parser<'src> {
     pub(crate) fn new(src: &'src str) -> Self {
-        let lexer = logos::Lexer::<'src, Token>::new(src)
-            .spanned()
-            .map(|(t, r)| (t, SourceSpan::from((r.start, r.len()))))
-            .collect_vec();
+        let lexer = tokenize(src).collect_vec();
 
         Parser {
             src,
@@ -302,7 +342,16 @@ impl<'src> Parser<'src> {
             self.bump();
             Ok(s)
         } else {
-            todo!()
+            let err = ParseError {
+                error: "Unrecognized token".into(),
+                src: self.src.into(),
+                err_span: self.current_span(),
+                label: Some(format!("Found {:?} expected `{s}`", self.current())),
+                help: None,
+            };
+            self.errors.push(err.clone());
+            self.bump();
+            Err(err)
         }
     }
     pub(crate) fn expect(&mut self, t: Token) -> Result<&'src str, ParseError> {
@@ -355,3 +404,130 @@ impl<'src> Parser<'src> {
         }
     }
 }
+
+/// An iterator over the entries of a `get-model` response, such as
+/// `(define-fun x () Int 3)`, which reads the response from a [`BufRead`]
+/// and parses one entry at a time.
+///
+/// Unlike parsing the response using
+/// [`GetModelResponse::parse`](crate::ast::GetModelResponse::parse), neither
+/// the text of the response nor all of its entries have to be in memory at
+/// once, but only the text of the entry being parsed.
+///
+/// ```
+/// # use smtlib_lowlevel::{ast::ModelResponse, ModelEntries};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let response = "(\n  (define-fun x () Int 3)\n  (define-fun y () Bool false)\n)\n";
+/// let mut names = vec![];
+/// for entry in ModelEntries::new(response.as_bytes()) {
+///     let ModelResponse::DefineFun(f) = entry? else { unreachable!() };
+///     names.push(f.0.to_string());
+/// }
+/// assert_eq!(names, ["x", "y"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ModelEntries<R> {
+    reader: R,
+    entry: Vec<u8>,
+    depth: usize,
+    state: ScanState,
+    done: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Code,
+    String,
+    QuotedSymbol,
+    Comment,
+}
+
+impl<R: BufRead> ModelEntries<R> {
+    /// Reads the entries of the `get-model` response from `reader`. Anything
+    /// following the response is left unread.
+    pub fn new(reader: R) -> Self {
+        ModelEntries {
+            reader,
+            entry: vec![],
+            depth: 0,
+            state: ScanState::Code,
+            done: false,
+        }
+    }
+    /// Reads until the end of the next entry, returning `false` if the
+    /// response ended instead.
+    fn scan_entry(&mut self) -> Result<bool, std::io::Error> {
+        loop {
+            let chunk = self.reader.fill_buf()?;
+            if chunk.is_empty() {
+                return if self.depth == 0 {
+                    Ok(false)
+                } else {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "the model response ended before all parentheses were closed",
+                    ))
+                };
+            }
+            let mut consumed = 0;
+            let mut complete = None;
+            for &b in chunk {
+                consumed += 1;
+                let in_entry = self.depth >= 2;
+                match (self.state, b) {
+                    (ScanState::Code, b'(') => self.depth += 1,
+                    (ScanState::Code, b')') => {
+                        self.depth = self.depth.saturating_sub(1);
+                        match self.depth {
+                            0 => complete = Some(false),
+                            1 => complete = Some(true),
+                            _ => {}
+                        }
+                    }
+                    (ScanState::Code, b'"') => self.state = ScanState::String,
+                    (ScanState::Code, b'|') => self.state = ScanState::QuotedSymbol,
+                    (ScanState::Code, b';') => self.state = ScanState::Comment,
+                    (ScanState::String, b'"')
+                    | (ScanState::QuotedSymbol, b'|')
+                    | (ScanState::Comment, b'\n') => self.state = ScanState::Code,
+                    _ => {}
+                }
+                if (in_entry || self.depth >= 2) && self.state != ScanState::Comment {
+                    self.entry.push(b);
+                }
+                if complete.is_some() {
+                    break;
+                }
+            }
+            self.reader.consume(consumed);
+            if let Some(complete) = complete {
+                return Ok(complete);
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ModelEntries<R> {
+    type Item = Result<ModelResponse, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        self.entry.clear();
+        let res = match self.scan_entry() {
+            Ok(true) => std::str::from_utf8(&self.entry)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e).into())
+                .and_then(|entry| Ok(ModelResponse::parse(entry)?)),
+            Ok(false) => {
+                self.done = true;
+                return None;
+            }
+            Err(e) => Err(e.into()),
+        };
+        self.done = res.is_err();
+        Some(res)
+    }
+}
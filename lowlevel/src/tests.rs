@@ -1,4 +1,21 @@
-use crate::{ast::Script, lexicon::SmtlibParse, parse::Parser};
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    ast::{
+        self, Attribute, AttributeValue, Command, GeneralResponse, Identifier, MatchCase, Pattern,
+        QualIdentifier, Script, SortedVar, Term, VarBinding,
+    },
+    backend::{is_complete_response, Backend, MockBackend, RecordingBackend},
+    lexicon::{
+        escape_string, try_escape_string, unescape_string, Keyword, Numeral, SmtlibParse, Symbol,
+    },
+    parse::Parser,
+    tokenize, Driver, ModelEntries, Token,
+};
 
 #[test]
 fn escaped_string() {
@@ -7,14 +24,558 @@ fn escaped_string() {
     )));
 }
 
+#[test]
+fn escaped_unicode_string() {
+    assert_eq!(
+        String::parse(&mut Parser::new(r#""caf\u{e9} ""ok""""#)).unwrap(),
+        r#""caf\u{e9} ""ok""""#
+    );
+    assert_eq!(escape_string("café \"ok\""), r#""caf\u{e9} ""ok""""#);
+    assert_eq!(escape_string("tab\t\\"), r#""tab\u{9}\u{5c}""#);
+    assert_eq!(try_escape_string("🦀").as_deref(), Some(r#""\u{1f980}""#));
+    assert_eq!(try_escape_string("a\u{30000}"), None);
+
+    assert_eq!(
+        unescape_string(r#""caf\u{e9} ""ok""""#).unwrap(),
+        "café \"ok\""
+    );
+    assert_eq!(unescape_string(r#""\u00e9\u{1F980}""#).unwrap(), "é🦀");
+    // Invalid escape sequences denote themselves
+    assert_eq!(
+        unescape_string(r#""\u{} \u{123456} \u12""#).unwrap(),
+        r"\u{} \u{123456} \u12"
+    );
+    assert_eq!(unescape_string("no quotes"), None);
+    for s in ["", "plain", "日本語 🦀", "\\u{61}", "\"\"", "\u{7f}\n"] {
+        assert_eq!(unescape_string(&escape_string(s)).unwrap(), s);
+    }
+}
+
 #[test]
 fn bubble_sort() {
     insta::assert_ron_snapshot!(Script::parse(include_str!("../examples/bubble_sort.smt2")));
 }
 
+#[test]
+fn complete_response_ignores_quoted_parens() {
+    assert!(is_complete_response("sat\n"));
+    assert!(is_complete_response("\"Hello, unmatched paren! :)\"\n"));
+    assert!(is_complete_response("(error \"expected ))\")\n"));
+    assert!(is_complete_response(
+        "((define-fun |a)| () String \"(\"\"(\"))\n"
+    ));
+    assert!(is_complete_response("(echo) ; (\n"));
+
+    assert!(!is_complete_response("\"an open string :(\n"));
+    assert!(!is_complete_response("((define-fun s () String \"))\n"));
+    assert!(!is_complete_response("((define-fun |a)) () Int 0))\n"));
+    assert!(!is_complete_response("(\n"));
+}
+
+#[test]
+fn set_option_round_trip() {
+    let numeral = |n: &str| Numeral(n.to_string());
+    for (src, option) in [
+        (
+            "(set-option :produce-unsat-cores true)",
+            ast::Option::ProduceUnsatCores(true),
+        ),
+        (
+            "(set-option :random-seed 42)",
+            ast::Option::RandomSeed(numeral("42")),
+        ),
+        (
+            "(set-option :timeout 1000)",
+            ast::Option::Timeout(numeral("1000")),
+        ),
+        (
+            "(set-option :verbosity 2)",
+            ast::Option::Verbosity(numeral("2")),
+        ),
+    ] {
+        let cmd = Command::SetOption(option);
+        assert_eq!(Command::parse(src).unwrap(), cmd);
+        assert_eq!(cmd.to_string(), src);
+    }
+}
+
+#[test]
+fn let_bound_variables_are_not_consts() {
+    let term = Term::parse("(let ((y (+ x 1))) (= y 5))").unwrap();
+    let consts: Vec<_> = term
+        .all_consts()
+        .into_iter()
+        .map(|q| q.to_string())
+        .collect();
+    assert!(consts.contains(&"x".to_string()));
+    assert!(!consts.contains(&"y".to_string()));
+    assert_eq!(term.clone().strip_sort(), term);
+}
+
+#[test]
+fn application_heads_are_not_unapplied_consts() {
+    let term = Term::parse("(= ((as const (Array Int Int)) 0) (f const))").unwrap();
+    let names = |consts: std::collections::HashSet<&QualIdentifier>| {
+        let mut names: Vec<_> = consts.into_iter().map(|q| q.to_string()).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(
+        names(term.all_consts()),
+        ["(as const (Array Int Int))", "=", "const", "f"]
+    );
+    assert_eq!(names(term.all_unapplied_consts()), ["const"]);
+}
+
+#[test]
+fn bound_variables_are_not_consts() {
+    for (src, free) in [
+        ("(forall ((x Int)) (> x y))", "y"),
+        ("(exists ((x Int)) (! (> x y) :named a))", "y"),
+        ("(match l ((nil y) ((cons x xs) (= x y))))", "y"),
+    ] {
+        let consts: Vec<_> = Term::parse(src)
+            .unwrap()
+            .all_consts()
+            .into_iter()
+            .map(|q| q.to_string())
+            .filter(|q| q == "x" || q == "xs" || q == "y")
+            .collect();
+        assert_eq!(consts, [free], "{src}");
+    }
+}
+
+#[test]
+fn shadowed_consts_are_free_outside_binder() {
+    let names = |src: &str| {
+        let mut names: Vec<_> = Term::parse(src)
+            .unwrap()
+            .all_consts()
+            .into_iter()
+            .map(|q| q.to_string())
+            .filter(|q| q == "x" || q == "y")
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names("(forall ((x Int)) (= x y))"), ["y"]);
+    assert_eq!(
+        names("(and (> x 0) (forall ((x Int)) (= x y)))"),
+        ["x", "y"]
+    );
+    assert!(names("(forall ((x Int)) (exists ((y Int)) (= x y)))").is_empty());
+}
+
+#[test]
+fn strip_sort_under_binders() {
+    let sym = |s: &str| Symbol(s.to_string());
+    let int = ast::Sort::Sort(Identifier::Simple(sym("Int")));
+    let sorted = |s: &str| {
+        Term::Identifier(QualIdentifier::Sorted(
+            Identifier::Simple(sym(s)),
+            int.clone(),
+        ))
+    };
+
+    // The same as the term below, but with `x`, `l`, and `z` qualified with their sort
+    let term = Term::Let(
+        vec![VarBinding(sym("y"), sorted("x"))],
+        Box::new(Term::Exists(
+            vec![SortedVar(sym("z"), int.clone())],
+            Box::new(Term::Annotation(
+                Box::new(Term::Match(
+                    Box::new(sorted("l")),
+                    vec![MatchCase(
+                        Pattern::Symbol(sym("nil")),
+                        Term::Application(
+                            QualIdentifier::Identifier(Identifier::Simple(sym("="))),
+                            vec![Term::parse("y").unwrap(), sorted("z")],
+                        ),
+                    )],
+                )),
+                vec![Attribute::WithValue(
+                    Keyword(":named".to_string()),
+                    AttributeValue::Symbol(sym("a")),
+                )],
+            )),
+        )),
+    );
+    assert_eq!(
+        term.strip_sort(),
+        Term::parse("(let ((y x)) (exists ((z Int)) (! (match l ((nil (= y z)))) :named a)))")
+            .unwrap()
+    );
+}
+
+#[test]
+fn check_sat_assuming_round_trip() {
+    let src = "(check-sat-assuming (a (not b)))";
+    let cmd = Command::CheckSatAssuming(vec![
+        ast::PropLiteral::Symbol(Symbol("a".to_string())),
+        ast::PropLiteral::Not(Symbol("b".to_string())),
+    ]);
+    assert_eq!(Command::parse(src).unwrap(), cmd);
+    assert_eq!(cmd.to_string(), src);
+}
+
+#[test]
+fn qualified_and_indexed_terms_round_trip() {
+    for src in [
+        "(as x Int)",
+        "(> (as x Int) 5)",
+        "(_ bv5 8)",
+        "((_ extract 3 0) x)",
+        "((as const (Array Int Int)) 0)",
+    ] {
+        assert_eq!(Term::parse(src).unwrap().to_string(), src);
+    }
+    assert!(matches!(
+        Term::parse("(as x Int)").unwrap(),
+        Term::Identifier(QualIdentifier::Sorted(..))
+    ));
+}
+
+#[test]
+fn parse_error_span() {
+    let src = "(declare-const x Int)\n(assert (> x 2))\n(declare-const 5 Int)\n";
+    let err = Script::parse(src).unwrap_err();
+    assert_eq!(err.span().offset(), src.find('5').unwrap());
+    assert_eq!(err.span().len(), 1);
+    assert_eq!(err.token(), "5");
+
+    // Reserved words are reported rather than panicking
+    let err = Command::parse("(check-sat-assuming (a) assert)").unwrap_err();
+    assert_eq!(err.token(), "assert");
+    let err = Term::parse("(let ((x 1)").unwrap_err();
+    assert_eq!(err.span().offset(), "(let ((x 1)".len());
+    assert!(err.token().is_empty());
+}
+
+#[test]
+fn tokenize_assert() {
+    let src = "(assert (= x 1))";
+    let tokens: Vec<_> = tokenize(src)
+        .map(|(t, span)| (t, &src[span.offset()..span.end()]))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (Token::LParen, "("),
+            (Token::Reserved, "assert"),
+            (Token::LParen, "("),
+            (Token::Symbol, "="),
+            (Token::Symbol, "x"),
+            (Token::Numeral, "1"),
+            (Token::RParen, ")"),
+            (Token::RParen, ")"),
+        ]
+    );
+}
+
+#[test]
+fn pretty_command() {
+    let clause =
+        |i: usize| format!("(or (> variable_{i} 0) (< variable_{i} 100) (= variable_{i} 50))");
+    let cmd = Command::parse(&format!("(assert (and {} (not {})))", clause(0), clause(1))).unwrap();
+    let pretty = cmd.pretty(4);
+    let lines: Vec<_> = pretty.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "(assert",
+            "    (and",
+            "        (or (> variable_0 0) (< variable_0 100) (= variable_0 50))",
+            "        (not (or (> variable_1 0) (< variable_1 100) (= variable_1 50)))))",
+        ]
+    );
+    assert_eq!(Command::parse(&pretty).unwrap(), cmd);
+
+    // Short commands stay on a single line
+    let cmd = Command::parse("(assert (and (or a b) c))").unwrap();
+    assert_eq!(cmd.pretty(2), cmd.to_string());
+}
+
+#[test]
+fn canonicalize() {
+    let canonical = |src: &str| Term::parse(src).unwrap().canonicalize();
+
+    let left = canonical("(+ (+ a b) c)");
+    let right = canonical("(+ c (+ b a))");
+    assert_eq!(left, right);
+    assert_eq!(left.to_string(), "(+ a b c)");
+
+    assert_eq!(canonical("(+ x 1 (+ 2 y))").to_string(), "(+ 3 x y)");
+    assert_eq!(canonical("(+ x (- 2) (* 2 1))").to_string(), "x");
+    assert_eq!(canonical("(* x 0 y)").to_string(), "0");
+    assert_eq!(canonical("(+ 1 (- 3))").to_string(), "(- 2)");
+    assert_eq!(canonical("(and p (and true q) p)").to_string(), "(and p q)");
+    assert_eq!(
+        canonical("(or p (not true) (or false q))").to_string(),
+        "(or p q)"
+    );
+    assert_eq!(canonical("(and p (not (or q true)))").to_string(), "false");
+    assert_eq!(canonical("(not (not (= y x)))"), canonical("(= x y)"));
+    // Non-commutative operators keep their argument order
+    assert_eq!(canonical("(- b a)").to_string(), "(- b a)");
+    assert_eq!(
+        canonical("(forall ((x Int)) (> (+ x (+ 1 1)) x))").to_string(),
+        "(forall ((x Int)) (> (+ 2 x) x))"
+    );
+}
+
+#[test]
+fn quote_symbols() {
+    let quoted = |name| Symbol::quoted(name).map(|s| s.0);
+    assert_eq!(quoted("x").as_deref(), Some("x"));
+    assert_eq!(quoted("x-1.y?").as_deref(), Some("x-1.y?"));
+    assert_eq!(quoted("a b").as_deref(), Some("|a b|"));
+    assert_eq!(quoted("1x").as_deref(), Some("|1x|"));
+    assert_eq!(quoted("assert").as_deref(), Some("|assert|"));
+    assert_eq!(quoted("a|b"), None);
+    assert_eq!(quoted("a\\b"), None);
+}
+
+#[test]
+fn declare_fun_round_trip() {
+    let src = "(declare-fun f (Int Bool) Int)";
+    let sort = |s: &str| ast::Sort::Sort(Identifier::Simple(Symbol(s.to_string())));
+    let cmd = Command::DeclareFun(
+        Symbol("f".to_string()),
+        vec![sort("Int"), sort("Bool")],
+        sort("Int"),
+    );
+    assert_eq!(Command::parse(src).unwrap(), cmd);
+    assert_eq!(cmd.to_string(), src);
+}
+
+#[test]
+fn reason_unknown_response() -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = Command::GetInfo(ast::InfoFlag::ReasonUnknown);
+    assert_eq!(cmd.to_string(), "(get-info :reason-unknown)");
+    let reasons = [
+        (
+            "(:reason-unknown incomplete)",
+            ast::ReasonUnknown::Incomplete,
+        ),
+        (
+            r#"(:reason-unknown "timeout")"#,
+            ast::ReasonUnknown::SExpr(ast::SExpr::SpecConstant(ast::SpecConstant::String(
+                r#""timeout""#.to_string(),
+            ))),
+        ),
+    ];
+    for (src, reason) in reasons {
+        assert_eq!(
+            cmd.parse_response(src)?,
+            Some(ast::SpecificSuccessResponse::GetInfoResponse(
+                ast::GetInfoResponse(vec![ast::InfoResponse::ReasonUnknown(reason)])
+            ))
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn optimization_commands() -> Result<(), Box<dyn std::error::Error>> {
+    for src in [
+        "(assert-soft (> x 2) :weight 3)",
+        "(minimize x)",
+        "(maximize (+ x y))",
+    ] {
+        assert_eq!(Command::parse(src)?.to_string(), src);
+    }
+    let res = Command::GetObjectives.parse_response("(objectives\n (x 3)\n ((+ x y) oo)\n)")?;
+    match res {
+        Some(ast::SpecificSuccessResponse::GetObjectivesResponse(res)) => {
+            assert_eq!(res.0.len(), 2);
+            assert_eq!(res.0[0].1.to_string(), "3");
+            assert_eq!(res.0[1].0.to_string(), "(+ x y)");
+        }
+        res => panic!("expected objectives, got {res:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn interpolant_command() -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = Command::parse("(get-interpolant I (> x 5))")?;
+    assert_eq!(cmd.to_string(), "(get-interpolant I (> x 5))");
+    match cmd.parse_response("(define-fun I () Bool (<= 6 x))")? {
+        Some(ast::SpecificSuccessResponse::GetInterpolantResponse(res)) => {
+            assert_eq!(res.0 .0 .0, "I");
+            assert_eq!(res.0 .3.to_string(), "(<= 6 x)");
+        }
+        res => panic!("expected an interpolant, got {res:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn model_entries_are_streamed() -> Result<(), Box<dyn std::error::Error>> {
+    /// Produces a model of `n` array entries on demand, counting the bytes
+    /// read so far
+    struct LargeModel {
+        n: usize,
+        next: usize,
+        pending: Vec<u8>,
+        read: Rc<Cell<usize>>,
+    }
+    impl std::io::Read for LargeModel {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                self.pending = match self.next {
+                    0 => b"(\n".to_vec(),
+                    i if i <= self.n => format!(
+                        "  (define-fun |a!{i}| () (Array Int Int) ; entry {i}\n    \
+                         (store ((as const (Array Int Int)) 0) {i} \"){i}(\"))\n"
+                    )
+                    .into_bytes(),
+                    i if i == self.n + 1 => b")\n".to_vec(),
+                    _ => return Ok(0),
+                };
+                self.next += 1;
+            }
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            self.read.set(self.read.get() + len);
+            Ok(len)
+        }
+    }
+
+    let n = 100_000;
+    let read = Rc::new(Cell::new(0));
+    let mut entries = ModelEntries::new(std::io::BufReader::new(LargeModel {
+        n,
+        next: 0,
+        pending: vec![],
+        read: read.clone(),
+    }));
+    let first = entries.next().unwrap()?;
+    assert_eq!(
+        first.to_string(),
+        "(define-fun |a!1| () (Array Int Int) (store ((as const (Array Int Int)) 0) 1 \")1(\"))"
+    );
+    // Only a single buffer of the response is read to produce the first entry
+    assert!(read.get() <= 8 * 1024);
+    assert_eq!(entries.count(), n - 1);
+    assert!(read.get() > n * 80);
+
+    let mut entries = ModelEntries::new("(define-fun x () Int".as_bytes());
+    assert!(entries.next().unwrap().is_err());
+    assert!(entries.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn logger_captures_commands_and_responses() -> Result<(), Box<dyn std::error::Error>> {
+    /// Responds to every command with `success`
+    struct Succeed;
+    impl Backend for Succeed {
+        fn exec(&mut self, _: &Command) -> Result<String, crate::Error> {
+            Ok("success\n".to_string())
+        }
+    }
+
+    let mut driver = Driver::new(Succeed, false)?;
+    let log = Arc::new(Mutex::new(vec![]));
+    let sink = log.clone();
+    driver.set_logger(move |s| sink.lock().unwrap().push(s.to_string()));
+    driver.exec(&Command::parse("(push 1)")?)?;
+
+    assert_eq!(*log.lock().unwrap(), ["(push 1)", "success"]);
+
+    Ok(())
+}
+
+#[test]
+fn exec_batch_is_a_single_backend_call() -> Result<(), Box<dyn std::error::Error>> {
+    /// Responds to every command with `success`, counting the calls
+    #[derive(Default)]
+    struct Count {
+        exec: usize,
+        batch: usize,
+    }
+    impl Backend for Count {
+        fn exec(&mut self, _: &Command) -> Result<String, crate::Error> {
+            self.exec += 1;
+            Ok("success\n".to_string())
+        }
+        fn exec_batch(&mut self, cmds: &[Command]) -> Result<Vec<String>, crate::Error> {
+            self.batch += 1;
+            Ok(vec!["success\n".to_string(); cmds.len()])
+        }
+    }
+
+    let mut driver = Driver::new(Count::default(), false)?;
+    driver.record_transcript(true);
+    let cmds = vec![Command::parse("(assert true)")?; 1000];
+    let responses = driver.exec_batch(&cmds)?;
+
+    assert_eq!(responses.len(), 1000);
+    assert!(responses.iter().all(|r| *r == GeneralResponse::Success));
+    // `Driver::new` executes a single `set-option`
+    assert_eq!((driver.backend.exec, driver.backend.batch), (1, 1));
+    assert_eq!(driver.transcript().matches("(assert true)").count(), 1000);
+
+    Ok(())
+}
+
+#[test]
+fn transcript_is_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+    let mut driver = Driver::new(MockBackend::new(|_| "success".to_string()), false)?;
+    driver.exec(&Command::parse("(push 1)")?)?;
+    assert_eq!(driver.transcript(), "");
+
+    driver.record_transcript(true);
+    driver.exec(&Command::parse("(pop 1)")?)?;
+    assert_eq!(driver.transcript(), "(pop 1)\n");
+
+    driver.clear_transcript();
+    assert_eq!(driver.transcript(), "");
+
+    Ok(())
+}
+
+#[test]
+fn recording_backend() -> Result<(), Box<dyn std::error::Error>> {
+    let backend = RecordingBackend::new(MockBackend::scripted(["sat", "((x 3))"]));
+    let mut driver = Driver::new(backend, false)?;
+    driver.exec(&Command::parse("(declare-const x Int)")?)?;
+    driver.exec_batch(&[Command::parse("(assert (> x 2))")?, Command::CheckSat])?;
+    driver.exec(&Command::parse("(get-value (x))")?)?;
+
+    let records = driver.backend().records();
+    let cmds: Vec<_> = records.iter().map(|(cmd, _)| cmd.to_string()).collect();
+    assert_eq!(
+        cmds,
+        [
+            "(set-option :print-success true)",
+            "(declare-const x Int)",
+            "(assert (> x 2))",
+            "(check-sat)",
+            "(get-value (x))",
+        ]
+    );
+    let responses: Vec<_> = records.iter().map(|(_, res)| res.as_str()).collect();
+    assert_eq!(
+        responses,
+        ["success", "success", "success", "sat", "((x 3))"]
+    );
+
+    Ok(())
+}
+
 #[cfg(feature = "z3")]
 mod z3 {
-    use crate::{ast::Command, backend::Z3Binary, Driver};
+    use crate::{
+        ast::{
+            Command, GeneralResponse, ModelResponse, SpecConstant, SpecificSuccessResponse, Term,
+        },
+        backend::{Backend, CallbackBackend, Z3Binary},
+        Driver,
+    };
 
     macro_rules! cmd {
         ($d:expr, $cmd:literal) => {
@@ -31,11 +592,105 @@ mod z3 {
 
         Ok(())
     }
+
+    #[test]
+    fn exec_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let mut d = Driver::new(Z3Binary::new("z3")?, false)?;
+
+        let mut cmds = vec![Command::parse("(declare-const x Int)")?];
+        cmds.extend((0..1000).map(|i| Command::parse(&format!("(assert (> x {i}))")).unwrap()));
+        cmds.push(Command::parse("(check-sat)")?);
+        let responses = d.exec_batch(&cmds)?;
+
+        assert_eq!(responses.len(), 1002);
+        assert!(responses[..1001]
+            .iter()
+            .all(|r| *r == GeneralResponse::Success));
+        assert_eq!(
+            responses[1001],
+            GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::CheckSatResponse(
+                crate::ast::CheckSatResponse::Sat
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn drop_reaps_process() -> Result<(), Box<dyn std::error::Error>> {
+        let mut handles = vec![];
+        for _ in 0..20 {
+            let backend = Z3Binary::new("z3")?;
+            handles.push(backend.kill_handle().unwrap());
+            let mut d = Driver::new(backend, false)?;
+            d.exec(&Command::parse("(check-sat)")?)?;
+        }
+
+        for handle in handles {
+            assert!(handle.try_wait()?.is_some());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_model_with_parens() -> Result<(), Box<dyn std::error::Error>> {
+        let mut d = Driver::new(Z3Binary::new("z3")?, false)?;
+
+        d.exec(&Command::parse("(declare-const s String)")?)?;
+        d.exec(&Command::parse(r#"(assert (= s "))(( :)"))"#)?)?;
+        d.exec(&Command::parse("(check-sat)")?)?;
+        let model = match d.exec(&Command::parse("(get-model)")?)? {
+            GeneralResponse::SpecificSuccessResponse(
+                SpecificSuccessResponse::GetModelResponse(model),
+            ) => model,
+            res => panic!("expected a model, got {res:?}"),
+        };
+        match model.0.as_slice() {
+            [ModelResponse::DefineFun(f)] => assert_eq!(
+                f.3,
+                Term::SpecConstant(SpecConstant::String(r#""))(( :)""#.to_string()))
+            ),
+            _ => panic!("expected exactly one definition, got {model:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn callback_backend() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: The callback only sees text, as it would when forwarding the
+        // commands to a solver in a web worker
+        let mut z3 = Z3Binary::new("z3")?;
+        let mut sent = vec![];
+        let backend = CallbackBackend::new(|src: &str| {
+            sent.push(src.to_string());
+            z3.exec(&Command::parse(src).unwrap()).unwrap()
+        });
+        let mut d = Driver::new(backend, false)?;
+
+        d.exec(&Command::parse("(declare-const x Int)")?)?;
+        d.exec(&Command::parse("(assert (and (> x 2) (< x 4)))")?)?;
+        assert_eq!(
+            d.exec(&Command::parse("(check-sat)")?)?,
+            GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::CheckSatResponse(
+                crate::ast::CheckSatResponse::Sat
+            ))
+        );
+        drop(d);
+        assert_eq!(sent.last().unwrap(), "(check-sat)");
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "z3-static")]
 mod z3_static {
-    use crate::{ast::Command, backend::Z3Static, Driver};
+    use crate::{
+        ast::{CheckSatResponse, Command, GeneralResponse, SpecificSuccessResponse},
+        backend::{Backend, Z3Static},
+        Driver,
+    };
 
     macro_rules! cmd {
         ($d:expr, $cmd:literal) => {
@@ -52,6 +707,133 @@ mod z3_static {
         cmd!(d, r#"(echo "Hello, world!")"#);
         cmd!(d, r#"(echo "Hello, unmatched paren! :)")"#);
 
+        Ok(())
+    }
+    #[test]
+    fn with_params() -> Result<(), Box<dyn std::error::Error>> {
+        let z3 = Z3Static::with_params(&[("smt.random_seed", "42"), ("model.compact", "false")])?;
+        let mut d = Driver::new(z3, false)?;
+
+        d.exec(&Command::parse("(declare-const x Int)")?)?;
+        d.exec(&Command::parse("(assert (> x 2))")?)?;
+        assert!(matches!(
+            d.exec(&Command::CheckSat)?,
+            GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::CheckSatResponse(
+                CheckSatResponse::Sat
+            ))
+        ));
+
+        assert!(Z3Static::with_params(&[("smt.random_seed\0", "42")]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn interior_nul() -> Result<(), Box<dyn std::error::Error>> {
+        let mut z3 = Z3Static::new(&None)?;
+
+        let res = z3.exec(&Command::Echo("\"nul \0 byte\"".to_string()));
+        assert!(matches!(
+            res,
+            Err(crate::Error::IO(e)) if e.kind() == std::io::ErrorKind::InvalidInput
+        ));
+        // The backend is still usable afterwards
+        assert_eq!(z3.exec(&Command::parse("(check-sat)")?)?.trim(), "sat");
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "socket")]
+mod socket {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{SocketAddr, TcpListener},
+        thread::JoinHandle,
+    };
+
+    use crate::{
+        ast::{Command, GeneralResponse, SpecificSuccessResponse},
+        backend::SocketBackend,
+        Driver,
+    };
+
+    /// Starts a server accepting a single connection, which answers every
+    /// line it receives with the next of `responses`. Each response is sent in
+    /// chunks of a few bytes, to check that the backend waits for the complete
+    /// response.
+    fn serve(responses: &'static [&'static str]) -> (SocketAddr, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut received = vec![];
+            for response in responses {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                received.push(line.trim_end().to_string());
+                for chunk in response.as_bytes().chunks(4) {
+                    writer.write_all(chunk).unwrap();
+                    writer.flush().unwrap();
+                }
+            }
+            received
+        });
+        (addr, handle)
+    }
+
+    const RESPONSES: &[&str] = &[
+        "success\n",
+        "sat\n",
+        "(\n  (define-fun x () Int\n    1)\n)\n",
+    ];
+
+    #[test]
+    fn canned_responses() -> Result<(), Box<dyn std::error::Error>> {
+        let (addr, server) = serve(RESPONSES);
+        let mut d = Driver::new(SocketBackend::connect(addr)?, false)?;
+
+        d.exec(&Command::parse("(check-sat)")?)?;
+        let res = d.exec(&Command::parse("(get-model)")?)?;
+        assert!(matches!(
+            res,
+            GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::GetModelResponse(
+                m
+            )) if m.0.len() == 1
+        ));
+
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "(set-option :print-success true)",
+                "(check-sat)",
+                "(get-model)"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async-socket")]
+    #[tokio::test]
+    async fn async_canned_responses() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::{backend::AsyncSocketBackend, AsyncDriver};
+
+        let (addr, server) = serve(RESPONSES);
+        let mut d = AsyncDriver::new(AsyncSocketBackend::connect(addr).await?).await?;
+
+        d.exec(&Command::parse("(check-sat)")?).await?;
+        let res = d.exec(&Command::parse("(get-model)")?).await?;
+        assert!(matches!(
+            res,
+            GeneralResponse::SpecificSuccessResponse(SpecificSuccessResponse::GetModelResponse(
+                m
+            )) if m.0.len() == 1
+        ));
+        server.join().unwrap();
+
         Ok(())
     }
 }
@@ -41,6 +41,7 @@ fn main() -> miette::Result<()> {
         ///
         /// ![This is a graph :)](https://smtlib.cs.uiowa.edu/Logics/logics.png)
         #[allow(nonstandard_style)]
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
         pub enum Logic {{
         "
     )
@@ -49,7 +50,6 @@ fn main() -> miette::Result<()> {
     let logics = logics()?;
 
     for Logic(name, attrs) in &logics {
-        println!("Debug logic {:?}, attrs {:?}", name, attrs);
         let language = attrs
             .iter()
             .find_map(|a| match a {
@@ -87,6 +87,10 @@ fn main() -> miette::Result<()> {
         .iter()
         .map(|Logic(name, _)| format!("Self::{name} => \"{name}\".fmt(f),"))
         .format("\n");
+    let from_name_impl = logics
+        .iter()
+        .map(|Logic(name, _)| format!("\"{name}\" => Self::{name},"))
+        .format("\n");
 
     writeln!(
         logic_file,
@@ -99,6 +103,81 @@ fn main() -> miette::Result<()> {
                 }}
             }}
         }}
+
+        impl Logic {{
+            /// The logic named `name`, or [`Logic::Custom`] if it is not one
+            /// of the predefined logics. This is the inverse of the
+            /// `Display` implementation.
+            pub fn from_name(name: &str) -> Logic {{
+                match name {{
+                    {from_name_impl}
+                    _ => Self::Custom(name.to_string()),
+                }}
+            }}
+        }}
+    "#
+    )
+    .into_diagnostic()?;
+
+    let theories_of = |attrs: &[LogicAttribute]| {
+        attrs
+            .iter()
+            .filter_map(|a| match a {
+                LogicAttribute::Theories(ts) => Some(ts.iter().map(|t| t.0.clone())),
+                _ => None,
+            })
+            .flatten()
+            .collect_vec()
+    };
+    let theories = logics
+        .iter()
+        .flat_map(|Logic(_, attrs)| theories_of(attrs))
+        .sorted()
+        .dedup()
+        .collect_vec();
+
+    let theory_variants = theories
+        .iter()
+        .map(|t| format!("/// The `{t}` theory.\n{t},"))
+        .format("\n");
+    let theories_impl = logics
+        .iter()
+        .map(|Logic(name, attrs)| {
+            let ts = theories_of(attrs)
+                .into_iter()
+                .map(|t| format!("Theory::{t}"))
+                .join(", ");
+            format!("Self::{name} => &[{ts}],")
+        })
+        .format("\n");
+
+    writeln!(
+        logic_file,
+        r#"
+        /// The theories which logics are built upon, as listed in the
+        /// `:theories` attribute of the logics.
+        ///
+        /// > [The theories are described in detail on the SMT-LIB
+        /// website.](https://smtlib.cs.uiowa.edu/theories.shtml)
+        #[allow(nonstandard_style)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Theory {{
+            /// The core theory of booleans, which is included in every logic.
+            Core,
+            {theory_variants}
+        }}
+
+        impl Logic {{
+            /// The theories the logic is built upon, not including the
+            /// [`Theory::Core`] which is part of every logic. For
+            /// [`Logic::Custom`] no theories are known.
+            pub fn theories(&self) -> &'static [Theory] {{
+                match self {{
+                    {theories_impl}
+                    Self::Custom(_) => &[],
+                }}
+            }}
+        }}
     "#
     )
     .into_diagnostic()?;
@@ -3,9 +3,9 @@ use miette::IntoDiagnostic;
 use smtlib::{
     and,
     backend::{Backend, Cvc5Binary, Z3Binary, Z3Static},
-    distinct, or,
+    distinct,
     terms::Sort,
-    Int, Logic, SatResultWithModel, Solver,
+    Int, Logic, Solver,
 };
 
 fn queens<B: Backend>(backend: B) -> miette::Result<()> {
@@ -17,7 +17,7 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
     let x5 = Int::from_name("x5");
     let x6 = Int::from_name("x6");
     let x7 = Int::from_name("x7");
-    let xs = [x0, x1, x2, x3, x4, x5, x6, x7];
+    let xs = [&x0, &x1, &x2, &x3, &x4, &x5, &x6, &x7];
 
     let n = Int::from_name("N");
 
@@ -28,46 +28,40 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
     solver.assert(n._eq(8))?;
 
     solver.assert(and([
-        and([x0.ge(0), x0.lt(n)]),
-        and([x1.ge(0), x1.lt(n)]),
-        and([x2.ge(0), x2.lt(n)]),
-        and([x3.ge(0), x3.lt(n)]),
-        and([x4.ge(0), x4.lt(n)]),
-        and([x5.ge(0), x5.lt(n)]),
-        and([x6.ge(0), x6.lt(n)]),
-        and([x7.ge(0), x7.lt(n)]),
+        x0.in_range(0, &n),
+        x1.in_range(0, &n),
+        x2.in_range(0, &n),
+        x3.in_range(0, &n),
+        x4.in_range(0, &n),
+        x5.in_range(0, &n),
+        x6.in_range(0, &n),
+        x7.in_range(0, &n),
     ]))?;
 
     solver.assert(distinct(xs))?;
 
     solver.assert(distinct([
-        x0 - 0,
-        x1 - 1,
-        x2 - 2,
-        x3 - 3,
-        x4 - 4,
-        x5 - 5,
-        x6 - 6,
-        x7 - 7,
+        &x0 - 0,
+        &x1 - 1,
+        &x2 - 2,
+        &x3 - 3,
+        &x4 - 4,
+        &x5 - 5,
+        &x6 - 6,
+        &x7 - 7,
     ]))?;
 
-    for i in 1.. {
-        match solver.check_sat_with_model()? {
-            SatResultWithModel::Unsat => {
-                eprintln!("No more solutions!");
-                break;
-            }
-            SatResultWithModel::Sat(model) => {
-                println!(
-                    "{i:5}: {}",
-                    xs.map(|x| model.eval(x).unwrap()).iter().format(",")
-                );
-
-                solver.assert(or(xs.map(|x| distinct([x.into(), model.eval(x).unwrap()]))))?;
-            }
-            SatResultWithModel::Unknown => todo!(),
-        }
+    let vars = xs.map(|x| x.clone().into());
+    for (i, model) in (1..).zip(solver.models(&vars)) {
+        let model = model?;
+        println!(
+            "{i:5}: {}",
+            xs.map(|x| model.eval(x.clone()).unwrap())
+                .iter()
+                .format(",")
+        );
     }
+    eprintln!("No more solutions!");
 
     Ok(())
 }
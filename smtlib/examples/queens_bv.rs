@@ -19,16 +19,16 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
     let x5 = BitVec::<4>::from_name("x5");
     let x6 = BitVec::<4>::from_name("x6");
     let x7 = BitVec::<4>::from_name("x7");
-    let xs = [x0, x1, x2, x3, x4, x5, x6, x7];
+    let xs = [&x0, &x1, &x2, &x3, &x4, &x5, &x6, &x7];
 
     let mut solver = Solver::new(backend, false)?;
 
     // solver.set_logic(Logic::QF_BV)?;
 
-    dbg!(BitVec::<4>::from(8));
+    dbg!(BitVec::<4>::from(8u64));
 
     solver.assert(and([
-        x0._eq(BitVec::from(8) % x0),
+        x0._eq(BitVec::from(8u64) % &x0),
         // x1._eq(x1 % 8),
         // x2._eq(x2 % 8),
         // x3._eq(x3 % 8),
@@ -60,12 +60,17 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
             SatResultWithModel::Sat(model) => {
                 println!(
                     "{i:5}: {}",
-                    xs.map(|x| model.eval(x).map(|x| x.to_string()).unwrap_or_default())
+                    xs.map(|x| model
+                        .eval(x.clone())
+                        .map(|x| x.to_string())
+                        .unwrap_or_default())
                         .iter()
                         .format(",")
                 );
 
-                solver.assert(or(xs.map(|x| distinct([x.into(), model.eval(x).unwrap()]))))?;
+                solver.assert(or(
+                    xs.map(|x| distinct([x.into(), model.eval(x.clone()).unwrap()]))
+                ))?;
             }
             SatResultWithModel::Unknown => todo!(),
         }
@@ -19,7 +19,7 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
     let x5 = BitVec::<8>::from_name("x5");
     let x6 = BitVec::<8>::from_name("x6");
     let x7 = BitVec::<8>::from_name("x7");
-    let xs = [x0, x1, x2, x3, x4, x5, x6, x7];
+    let xs = [&x0, &x1, &x2, &x3, &x4, &x5, &x6, &x7];
 
     let mut solver = Solver::new(backend, false)?;
 
@@ -29,43 +29,43 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
 
     solver.assert(and([
         // 0
-        (x0 & x1)._eq(0),
-        (x0 & x2)._eq(0),
-        (x0 & x3)._eq(0),
-        (x0 & x4)._eq(0),
-        (x0 & x5)._eq(0),
-        (x0 & x6)._eq(0),
-        (x0 & x7)._eq(0),
+        (&x0 & &x1)._eq(0u64),
+        (&x0 & &x2)._eq(0u64),
+        (&x0 & &x3)._eq(0u64),
+        (&x0 & &x4)._eq(0u64),
+        (&x0 & &x5)._eq(0u64),
+        (&x0 & &x6)._eq(0u64),
+        (&x0 & &x7)._eq(0u64),
         // 1
-        (x1 & x2)._eq(0),
-        (x1 & x3)._eq(0),
-        (x1 & x4)._eq(0),
-        (x1 & x5)._eq(0),
-        (x1 & x6)._eq(0),
-        (x1 & x7)._eq(0),
+        (&x1 & &x2)._eq(0u64),
+        (&x1 & &x3)._eq(0u64),
+        (&x1 & &x4)._eq(0u64),
+        (&x1 & &x5)._eq(0u64),
+        (&x1 & &x6)._eq(0u64),
+        (&x1 & &x7)._eq(0u64),
         // 2
-        (x2 & x3)._eq(0),
-        (x2 & x4)._eq(0),
-        (x2 & x5)._eq(0),
-        (x2 & x6)._eq(0),
-        (x2 & x7)._eq(0),
+        (&x2 & &x3)._eq(0u64),
+        (&x2 & &x4)._eq(0u64),
+        (&x2 & &x5)._eq(0u64),
+        (&x2 & &x6)._eq(0u64),
+        (&x2 & &x7)._eq(0u64),
         // 3
-        (x3 & x4)._eq(0),
-        (x3 & x5)._eq(0),
-        (x3 & x6)._eq(0),
-        (x3 & x7)._eq(0),
+        (&x3 & &x4)._eq(0u64),
+        (&x3 & &x5)._eq(0u64),
+        (&x3 & &x6)._eq(0u64),
+        (&x3 & &x7)._eq(0u64),
         // 4
-        (x4 & x5)._eq(0),
-        (x4 & x6)._eq(0),
-        (x4 & x7)._eq(0),
+        (&x4 & &x5)._eq(0u64),
+        (&x4 & &x6)._eq(0u64),
+        (&x4 & &x7)._eq(0u64),
         // 5
-        (x5 & x6)._eq(0),
-        (x5 & x7)._eq(0),
+        (&x5 & &x6)._eq(0u64),
+        (&x5 & &x7)._eq(0u64),
         // 6
-        (x6 & x7)._eq(0),
+        (&x6 & &x7)._eq(0u64),
     ]))?;
 
-    solver.assert((x0 | x1 | x2 | x3 | x4 | x5 | x6 | x7)._eq(0b11111111))?;
+    solver.assert((&x0 | &x1 | &x2 | &x3 | &x4 | &x5 | &x6 | &x7)._eq(0b11111111u64))?;
 
     for i in 1.. {
         match solver.check_sat_with_model()? {
@@ -76,10 +76,14 @@ fn queens<B: Backend>(backend: B) -> miette::Result<()> {
             SatResultWithModel::Sat(model) => {
                 println!(
                     "{i:5}: {}",
-                    xs.map(|x| model.eval(x).unwrap()).iter().format(",")
+                    xs.map(|x| model.eval(x.clone()).unwrap())
+                        .iter()
+                        .format(",")
                 );
 
-                solver.assert(or(xs.map(|x| distinct([x.into(), model.eval(x).unwrap()]))))?;
+                solver.assert(or(
+                    xs.map(|x| distinct([x.into(), model.eval(x.clone()).unwrap()]))
+                ))?;
             }
             SatResultWithModel::Unknown => todo!(),
         }
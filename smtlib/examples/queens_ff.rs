@@ -24,7 +24,7 @@ fn queens_ff<B: Backend>(backend: B) -> miette::Result<()> {
     let x5 = FieldElement::from_name("x5");
     let x6 = FieldElement::from_name("x6");
     let x7 = FieldElement::from_name("x7");
-    let xs = [x0, x1, x2, x3, x4, x5, x6, x7];
+    let xs = [&x0, &x1, &x2, &x3, &x4, &x5, &x6, &x7];
 
     let n = FieldElement::from_name("N");
 
@@ -56,10 +56,12 @@ fn queens_ff<B: Backend>(backend: B) -> miette::Result<()> {
             SatResultWithModel::Sat(model) => {
                 println!(
                     "{i:5}: {}",
-                    xs.map(|x| model.eval(x).unwrap()).iter().format(",")
+                    xs.map(|x| model.eval(x.clone()).unwrap()).iter().format(",")
                 );
 
-                solver.assert(or(xs.map(|x| distinct([x.into(), model.eval(x).unwrap()]))))?;
+                solver.assert(or(
+                    xs.map(|x| distinct([x.into(), model.eval(x.clone()).unwrap()]))
+                ))?;
             }
             SatResultWithModel::Unknown => todo!(),
         }
@@ -1,13 +1,13 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{HashMap, HashSet};
 
 use smtlib_lowlevel::{
-    ast::{self, Identifier, QualIdentifier},
+    ast::{self, Identifier},
     backend,
     lexicon::Symbol,
     AsyncDriver,
 };
 
-use crate::{Bool, Error, Logic, Model, SatResult, SatResultWithModel};
+use crate::{solver::declarations, Bool, Error, Logic, Model, SatResult, SatResultWithModel};
 
 /// The [`AsyncSolver`] type is the primary entrypoint to interaction with the
 /// solver. Checking for validity of a set of assertions requires:
@@ -40,6 +40,7 @@ use crate::{Bool, Error, Logic, Model, SatResult, SatResultWithModel};
 pub struct AsyncSolver<B> {
     driver: AsyncDriver<B>,
     decls: HashMap<Identifier, ast::Sort>,
+    tuples: HashSet<String>,
 }
 
 impl<B> AsyncSolver<B>
@@ -54,6 +55,7 @@ where
         Ok(Self {
             driver: AsyncDriver::new(backend).await?,
             decls: Default::default(),
+            tuples: Default::default(),
         })
     }
     /// Explicitly sets the logic for the solver. For some backends this is not
@@ -64,9 +66,9 @@ where
         let cmd = ast::Command::SetLogic(Symbol(logic.to_string()));
         match self.driver.exec(&cmd).await? {
             ast::GeneralResponse::Success => Ok(()),
-            ast::GeneralResponse::SpecificSuccessResponse(_) => todo!(),
-            ast::GeneralResponse::Unsupported => todo!(),
-            ast::GeneralResponse::Error(_) => todo!(),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
     /// Adds the constraint of `b` as an assertion to the solver. To check for
@@ -74,30 +76,15 @@ where
     /// [`AsyncSolver::check_sat_with_model`].
     pub async fn assert(&mut self, b: Bool) -> Result<(), Error> {
         let term = ast::Term::from(b);
-        for q in term.all_consts() {
-            match q {
-                QualIdentifier::Identifier(_) => {}
-                QualIdentifier::Sorted(i, s) => match self.decls.entry(i.clone()) {
-                    Entry::Occupied(stored) => assert_eq!(s, stored.get()),
-                    Entry::Vacant(v) => {
-                        v.insert(s.clone());
-                        match i {
-                            Identifier::Simple(sym) => {
-                                self.driver
-                                    .exec(&ast::Command::DeclareConst(sym.clone(), s.clone()))
-                                    .await?;
-                            }
-                            Identifier::Indexed(_, _) => todo!(),
-                        }
-                    }
-                },
-            }
+        for cmd in declarations(&mut self.decls, &mut self.tuples, &term, &[])? {
+            self.driver.exec(&cmd).await?;
         }
         let cmd = ast::Command::Assert(term);
         match self.driver.exec(&cmd).await? {
             ast::GeneralResponse::Success => Ok(()),
-            ast::GeneralResponse::Error(e) => Err(Error::Smt(e, cmd.to_string())),
-            _ => todo!(),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
     /// Checks for satisfiability of the assertions sent to the solver using
@@ -105,18 +92,19 @@ where
     ///
     /// If you are interested in producing a model satisfying the assertions
     /// check out [`AsyncSolver::check_sat`].
+    ///
+    /// Since the returned future can be awaited alongside others, it can be
+    /// combined with for example `tokio::time::timeout` to bound the time
+    /// spent waiting for the solver.
     pub async fn check_sat(&mut self) -> Result<SatResult, Error> {
         let cmd = ast::Command::CheckSat;
         match self.driver.exec(&cmd).await? {
             ast::GeneralResponse::SpecificSuccessResponse(
                 ast::SpecificSuccessResponse::CheckSatResponse(res),
-            ) => Ok(match res {
-                ast::CheckSatResponse::Sat => SatResult::Sat,
-                ast::CheckSatResponse::Unsat => SatResult::Unsat,
-                ast::CheckSatResponse::Unknown => SatResult::Unknown,
-            }),
-            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg, format!("{cmd}"))),
-            res => todo!("{res:?}"),
+            ) => Ok(res.into()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
     /// Checks for satisfiability of the assertions sent to the solver using
@@ -138,11 +126,63 @@ where
     /// > **NOTE:** This must only be called after having called
     /// > [`AsyncSolver::check_sat`] and it returning [`SatResult::Sat`].
     pub async fn get_model(&mut self) -> Result<Model, Error> {
-        match self.driver.exec(&ast::Command::GetModel).await? {
+        let cmd = ast::Command::GetModel;
+        match self.driver.exec(&cmd).await? {
             ast::GeneralResponse::SpecificSuccessResponse(
                 ast::SpecificSuccessResponse::GetModelResponse(model),
-            ) => Ok(Model::new(model)),
-            res => todo!("{res:?}"),
+            ) => Model::new(model),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::{AsyncBackend, Backend, Z3Binary, Z3BinaryAsync};
+
+    use crate::{terms::Sort, AsyncSolver, Int, SatResult};
+
+    /// Runs a synchronous backend as an asynchronous one, blocking on every
+    /// command.
+    struct Blocking<B>(B);
+
+    #[async_trait::async_trait(?Send)]
+    impl<B: Backend> AsyncBackend for Blocking<B> {
+        async fn exec(
+            &mut self,
+            cmd: &smtlib_lowlevel::ast::Command,
+        ) -> Result<String, smtlib_lowlevel::Error> {
+            self.0.exec(cmd)
         }
     }
+
+    #[tokio::test]
+    async fn trivial_sat() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = AsyncSolver::new(Blocking(Z3Binary::new("z3")?)).await?;
+
+        let x = Int::from_name("x");
+        solver.assert(x._eq(12)).await?;
+        assert!(matches!(solver.check_sat().await?, SatResult::Sat));
+
+        let model = solver.get_model().await?;
+        assert_eq!(model.eval(x).unwrap().to_string(), "12");
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn z3_binary_async() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = AsyncSolver::new(Z3BinaryAsync::new("z3")?).await?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2)).await?;
+        solver.assert(x.lt(4)).await?;
+        assert!(matches!(solver.check_sat().await?, SatResult::Sat));
+
+        let model = solver.get_model().await?;
+        assert_eq!(model.eval(x).unwrap().to_string(), "3");
+
+        Ok(())
+    }
 }
@@ -6,27 +6,35 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 use smtlib_lowlevel::ast;
-use terms::Const;
 pub use terms::Sort;
+use terms::{Const, Label};
 
 pub use backend::Backend;
-pub use logics::Logic;
+pub use logics::{Logic, Theory};
 pub use smtlib_lowlevel::backend;
 
 #[cfg(feature = "async")]
 mod async_solver;
 mod logics;
+mod pool;
+pub mod race;
+mod sat;
 mod solver;
 pub mod terms;
 pub mod theories;
 
 #[cfg(feature = "async")]
 pub use async_solver::AsyncSolver;
-pub use solver::Solver;
-pub use theories::{core::*, fixed_size_bit_vectors::*, ints::*, reals::*};
+pub use pool::{PooledSolver, SolverPool};
+pub use sat::SatSolver;
+pub use solver::{Solver, SolverBuilder};
+pub use theories::{
+    arrays::*, core::*, fixed_size_bit_vectors::*, floats::*, ints::*, reals::*, sets::*,
+    strings::*, tuples::*,
+};
 
 /// The satisfiability result produced by a solver
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SatResult {
     /// The solver produced `unsat`
     Unsat,
@@ -36,6 +44,41 @@ pub enum SatResult {
     Unknown,
 }
 
+impl SatResult {
+    /// Determines if the result is `sat`
+    pub fn is_sat(self) -> bool {
+        self == SatResult::Sat
+    }
+    /// Determines if the result is `unsat`
+    pub fn is_unsat(self) -> bool {
+        self == SatResult::Unsat
+    }
+    /// Determines if the result is `unknown`
+    pub fn is_unknown(self) -> bool {
+        self == SatResult::Unknown
+    }
+}
+
+impl From<ast::CheckSatResponse> for SatResult {
+    fn from(res: ast::CheckSatResponse) -> Self {
+        match res {
+            ast::CheckSatResponse::Sat => SatResult::Sat,
+            ast::CheckSatResponse::Unsat => SatResult::Unsat,
+            ast::CheckSatResponse::Unknown => SatResult::Unknown,
+        }
+    }
+}
+
+impl From<&SatResultWithModel> for SatResult {
+    fn from(res: &SatResultWithModel) -> Self {
+        match res {
+            SatResultWithModel::Unsat => SatResult::Unsat,
+            SatResultWithModel::Sat(_) => SatResult::Sat,
+            SatResultWithModel::Unknown => SatResult::Unknown,
+        }
+    }
+}
+
 impl std::fmt::Display for SatResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -74,14 +117,49 @@ impl SatResultWithModel {
             }),
         }
     }
+    /// Expect the result to be `unsat`. If not `unsat`, returns an error,
+    /// which for a `sat` result includes the satisfying model.
+    pub fn expect_unsat(self) -> Result<(), Error> {
+        match self {
+            SatResultWithModel::Unsat => Ok(()),
+            SatResultWithModel::Sat(m) => Err(Error::UnexpectedModel {
+                model: m.to_string(),
+            }),
+            SatResultWithModel::Unknown => Err(Error::UnexpectedSatResult {
+                expected: SatResult::Unsat,
+                actual: SatResult::Unknown,
+            }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::terms::{forall, Sort};
+    use crate::terms::{forall, forall_with, Dynamic, Sort};
 
     use super::*;
 
+    #[test]
+    fn model_from_reader() -> Result<(), Box<dyn std::error::Error>> {
+        let response = (0..1000)
+            .map(|i| format!("(define-fun x{i} () Int {i})"))
+            .join("\n");
+        let model = Model::from_reader(format!("(\n{response}\n)\n").as_bytes())?;
+        assert_eq!(model.iter().count(), 1000);
+        assert_eq!(
+            i64::try_from(model.eval(Int::from_name("x42")).unwrap())?,
+            42
+        );
+
+        assert!(Model::from_reader("((define-fun x () Int".as_bytes()).is_err());
+        assert!(matches!(
+            Model::from_reader("((define-fun-rec f ((x Int)) Int x))".as_bytes()),
+            Err(Error::UnexpectedTerm { .. })
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn int_math() {
         let x = Int::from_name("x");
@@ -89,18 +167,207 @@ mod tests {
         // let x_named = x.labeled();
         let mut z = 12 + y * 4;
         z += 3;
-        let w = x * x + z;
+        let w = &x * &x + z;
         println!("{w}");
     }
 
+    #[test]
+    fn sat_result_predicates() {
+        assert!(SatResult::Sat.is_sat());
+        assert!(SatResult::Unsat.is_unsat());
+        assert!(SatResult::Unknown.is_unknown());
+        assert!(!SatResult::Sat.is_unsat());
+        assert_eq!(
+            SatResult::from(ast::CheckSatResponse::Unsat),
+            SatResult::Unsat
+        );
+        assert_eq!(
+            SatResult::from(&SatResultWithModel::Unknown),
+            SatResult::Unknown
+        );
+    }
+
+    #[test]
+    fn solver_error_location() {
+        let err = SolverError::from(r#""line 3 column 12: unknown constant x""#.to_string());
+        assert_eq!(err.line, Some(3));
+        assert_eq!(err.column, Some(12));
+        assert_eq!(err.message, "unknown constant x");
+
+        let err = SolverError::from(
+            r#""Parse Error: <stdin>:1.9: Symbol 'y' not declared as a variable""#.to_string(),
+        );
+        assert_eq!((err.line, err.column), (Some(1), Some(9)));
+        assert_eq!(err.message, "Symbol 'y' not declared as a variable");
+
+        let raw = r#""model is not available""#;
+        let err = SolverError::from(raw.to_string());
+        assert_eq!((err.line, err.column), (None, None));
+        assert_eq!(err.message, "model is not available");
+        assert_eq!(err.to_string(), raw);
+    }
+
+    #[test]
+    fn smt_error_from_solver() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+        let cmd = smtlib_lowlevel::ast::Command::parse("(assert (> x 1))")?;
+        let err = match solver.driver.exec(&cmd)? {
+            ast::GeneralResponse::Error(e) => SolverError::from(e),
+            res => panic!("expected an error, got {res:?}"),
+        };
+        assert_eq!(err.line, Some(1));
+        assert!(err.column.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn logic_introspection() {
+        assert!(Logic::QF_IDL.is_quantifier_free());
+        assert!(!Logic::LIA.is_quantifier_free());
+        assert_eq!(
+            Logic::QF_AUFLIA.theories(),
+            [Theory::Ints, Theory::ArraysEx]
+        );
+        assert!(Logic::QF_UF.theories().is_empty());
+        assert!(Logic::QF_UF.supports(Theory::Core));
+        assert!(Logic::QF_BV.supports(Theory::FixedSizeBitVectors));
+        assert!(!Logic::QF_BV.supports(Theory::Ints));
+    }
+
+    #[test]
+    fn from_name_quotes_only_when_necessary() {
+        assert_eq!(Int::from_name("x").to_string(), "(as x Int)");
+        assert_eq!(Int::from_name("a b").name(), "|a b|");
+    }
+
+    #[test]
+    fn model_iter() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x._eq(1))?;
+        solver.assert(y._eq(2))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        let mut names: Vec<_> = model.iter().map(|(name, _, _)| name).collect();
+        names.sort();
+        assert_eq!(names, ["x", "y"]);
+        assert_eq!(model.get("y").unwrap().to_string(), "2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn expect_unsat() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+        solver.assert(x.gt(3))?;
+        solver.assert(x.lt(2))?;
+        solver.check_sat_with_model()?.expect_unsat()?;
+
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+        solver.assert(x.gt(3))?;
+        let err = solver.check_sat_with_model()?.expect_unsat().unwrap_err();
+        assert!(matches!(&err, Error::UnexpectedModel { model } if model.contains("x: ")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn let_binding() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(terms::let_binding([("y", (&x + 1).into())], y._eq(5)))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(x).unwrap().to_string(), "4");
+        assert!(model.eval(y).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_const_with_sort() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Dynamic::from_name_with_sort("x", Int::sort());
+        assert_eq!(Dynamic::from(Int::from(3)).sort_of(), Some(&Int::sort()));
+        solver.assert(x._eq(Int::from(3)))?;
+        assert!(solver.transcript().contains("(declare-const x Int)"));
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(x).unwrap().to_string(), "3");
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_dynamic() {
+        let b = Dynamic::from(Bool::from(true));
+        assert!(matches!(
+            Int::try_from_dynamic(b.clone()),
+            Err(Error::SortMismatch { .. })
+        ));
+        assert!(Bool::try_from_dynamic(b).is_ok());
+        assert!(Int::try_from_dynamic(ast::Term::from(Int::from(1)).into()).is_ok());
+    }
+
+    #[test]
+    fn generic_ite() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x._eq(5))?;
+        solver.assert(y._eq(terms::ite::<Int>(x.gt(0), 1, -Int::from(1))))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(y).unwrap())?, 1);
+
+        Ok(())
+    }
+
     #[test]
     fn quantifiers() {
         let x = Int::from_name("x");
         let y = Int::from_name("y");
 
-        let res = forall((x, y), (x + 2)._eq(y));
+        let res = forall((x.clone(), y.clone()), (x + 2)._eq(y));
         println!("{}", ast::Term::from(res));
     }
+
+    #[test]
+    fn quantify_with_closure() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(backend::Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        solver.assert(forall_with(|x: Const<Int>| (&x + 0)._eq(&x)))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Sat));
+        assert!(!solver.transcript().contains("declare-const"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn quantify_over_array() {
+        let xs: [_; 10] = std::array::from_fn(|i| Int::from_name(format!("x{i}")));
+        let res = forall(xs.clone(), and(xs.each_ref().map(|x| x.ge(0))));
+        match ast::Term::from(res) {
+            ast::Term::Forall(vars, _) => assert_eq!(vars.len(), 10),
+            t => panic!("expected a forall, got {t}"),
+        }
+
+        let res = forall(xs.to_vec(), Bool::from(true));
+        assert!(ast::Term::from(res)
+            .to_string()
+            .starts_with("(forall ((x0 Int) (x1 Int)"));
+    }
 }
 
 /// An error that occurred during any stage of using `smtlib`.
@@ -121,7 +388,11 @@ pub enum Error {
     /// ```ignore
     /// (error "the error goes here")
     /// ```
-    Smt(String, String),
+    Smt(SolverError, String),
+    #[error("The solver does not support {0}")]
+    /// Occurs when the solver responds with `unsupported` to a command, for
+    /// example when setting a logic it does not know.
+    Unsupported(String),
     #[error("Expected the model to be {expected} but was {actual}")]
     /// Can occur by calling [`SatResultWithModel::expect_sat`] for example.
     UnexpectedSatResult {
@@ -130,6 +401,157 @@ pub enum Error {
         /// The actual sat result
         actual: SatResult,
     },
+    #[error("Expected the model to be unsat but was sat with the model {model}")]
+    /// Occurs when calling [`SatResultWithModel::expect_unsat`] on a `sat`
+    /// result, carrying the offending model.
+    UnexpectedModel {
+        /// The satisfying model, formatted as by [`Model`]'s `Display`
+        model: String,
+    },
+    #[error("Expected a term of sort {expected} but it was of sort {actual}")]
+    /// Occurs when casting a [`Dynamic`](terms::Dynamic) term to a term of a
+    /// different sort using [`Sort::try_from_dynamic`].
+    SortMismatch {
+        /// The sort being cast to
+        expected: String,
+        /// The sort of the term
+        actual: String,
+    },
+    #[error("Unexpected response {response} after running {cmd}")]
+    /// Occurs when the solver responds to a command with a well-formed response
+    /// which is not a valid response to that command, such as `success` to
+    /// `check-sat`.
+    UnexpectedResponse {
+        /// The command sent to the solver
+        cmd: String,
+        /// The response of the solver
+        response: String,
+    },
+    #[error("The term {term} {reason}, which is not allowed in the logic {logic}")]
+    /// Occurs when asserting a term using features which are not part of the
+    /// logic set by [`Solver::set_logic`], if enabled using
+    /// [`SolverBuilder::validate_logic`].
+    UnsupportedByLogic {
+        /// The logic of the solver
+        logic: String,
+        /// The offending term
+        term: String,
+        /// A description of what the term uses, which the logic does not allow
+        reason: String,
+    },
+    #[error("The string {value:?} contains characters above U+2FFFF, which are not part of the theory of strings")]
+    /// Occurs when constructing a [`Str`] literal using
+    /// [`Str::try_from_str`] from a string it cannot represent.
+    InvalidString {
+        /// The string which could not be represented
+        value: String,
+    },
+    #[error("The option {option} is required but is not enabled")]
+    /// Occurs when calling a method requiring an option which is disabled by
+    /// default, such as [`Solver::get_proof`] without enabling
+    /// [`SolverBuilder::produce_proofs`].
+    OptionDisabled {
+        /// The name of the required option
+        option: &'static str,
+    },
+    #[error("The transcript of the solver is not recorded")]
+    /// Occurs when calling [`Solver::fork`] on a solver which has not recorded
+    /// its complete transcript, see [`SolverBuilder::record_transcript`].
+    TranscriptNotRecorded,
+    #[error("Expected {expected} but got the term {term}")]
+    /// Occurs when converting a term, for example extracted from a [`Model`],
+    /// to a Rust value which it does not represent.
+    UnexpectedTerm {
+        /// A description of the expected value
+        expected: &'static str,
+        /// The term which could not be converted
+        term: String,
+    },
+}
+
+impl Error {
+    /// The error for the solver responding with `res` to `cmd`, when `res` is
+    /// not a valid response to it.
+    pub(crate) fn unexpected_response(cmd: &ast::Command, res: ast::GeneralResponse) -> Self {
+        Error::UnexpectedResponse {
+            cmd: cmd.to_string(),
+            response: res.to_string(),
+        }
+    }
+}
+
+/// The message of an `(error "...")` response, together with the location
+/// the solver reported the error at, if any.
+///
+/// Locations are recognized in the formats used by z3, such as `line 3
+/// column 12: unknown constant x`, and cvc5, such as `Parse Error:
+/// <stdin>:3.12: ...`. For errors in other formats, `line` and `column` are
+/// `None`, and `message` is the entire error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverError {
+    /// The error message, without the location
+    pub message: String,
+    /// The line the error occurred on, starting at 1
+    pub line: Option<usize>,
+    /// The column the error occurred at
+    pub column: Option<usize>,
+    /// The error exactly as reported by the solver
+    pub raw: String,
+}
+
+impl SolverError {
+    /// Splits `line N column M: message` as reported by z3
+    fn z3_location(msg: &str) -> Option<(usize, usize, &str)> {
+        let rest = msg.strip_prefix("line ")?;
+        let (line, rest) = rest.split_once(" column ")?;
+        let (column, message) = rest.split_once(':')?;
+        Some((
+            line.parse().ok()?,
+            column.parse().ok()?,
+            message.trim_start(),
+        ))
+    }
+    /// Splits `Parse Error: <file>:N.M: message` as reported by cvc5
+    fn cvc5_location(msg: &str) -> Option<(usize, usize, &str)> {
+        let rest = msg.strip_prefix("Parse Error: ")?;
+        let (_file, rest) = rest.split_once(':')?;
+        let (location, message) = rest.split_once(':')?;
+        let (line, column) = location.split_once('.')?;
+        Some((
+            line.parse().ok()?,
+            column.parse().ok()?,
+            message.trim_start(),
+        ))
+    }
+}
+
+impl From<String> for SolverError {
+    fn from(raw: String) -> Self {
+        let msg = match raw.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(msg) => msg.replace("\"\"", "\""),
+            None => raw.clone(),
+        };
+        match Self::z3_location(&msg).or_else(|| Self::cvc5_location(&msg)) {
+            Some((line, column, message)) => SolverError {
+                message: message.to_string(),
+                line: Some(line),
+                column: Some(column),
+                raw,
+            },
+            None => SolverError {
+                message: msg,
+                line: None,
+                column: None,
+                raw,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
 }
 
 /// A [`Model`] contains the values of all named constants returned through
@@ -138,9 +560,12 @@ pub enum Error {
 /// The two most common usages of [`Model`] is to:
 /// - Extract values for constants using [`Model::eval`].
 /// - Print out the produced model using `println!("{model}")`.
+#[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Model {
-    values: HashMap<String, ast::Term>,
+    values: HashMap<String, (ast::Sort, ast::Term)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    labels: HashMap<String, ast::Term>,
 }
 
 impl std::fmt::Debug for Model {
@@ -155,30 +580,70 @@ impl std::fmt::Display for Model {
             "{{ {} }}",
             self.values
                 .iter()
-                .map(|(n, t)| format!("{n}: {t}"))
+                .map(|(n, (_, t))| format!("{n}: {t}"))
                 .format(", ")
         )
     }
 }
 
 impl Model {
-    fn new(model: ast::GetModelResponse) -> Self {
-        Self {
+    fn new(model: ast::GetModelResponse) -> Result<Self, Error> {
+        Ok(Self {
             values: model
                 .0
                 .into_iter()
-                .map(|res| match res {
-                    ast::ModelResponse::DefineFun(f) => (f.0 .0.trim_matches('|').into(), f.3),
-                    ast::ModelResponse::DefineFunRec(_) => todo!(),
-                    ast::ModelResponse::DefineFunsRec(_, _) => todo!(),
-                })
-                .collect(),
+                .map(Self::entry)
+                .collect::<Result<_, _>>()?,
+            labels: HashMap::new(),
+        })
+    }
+    /// The name and value of an entry of a model. Recursive definitions are
+    /// not supported, and result in an error.
+    fn entry(res: ast::ModelResponse) -> Result<(String, (ast::Sort, ast::Term)), Error> {
+        match res {
+            ast::ModelResponse::DefineFun(f) => Ok((f.0 .0.trim_matches('|').into(), (f.2, f.3))),
+            res @ (ast::ModelResponse::DefineFunRec(_)
+            | ast::ModelResponse::DefineFunsRec(_, _)) => Err(Error::UnexpectedTerm {
+                expected: "a model entry defined using define-fun",
+                term: res.to_string(),
+            }),
         }
     }
+    /// Reads a model from the text of a `get-model` response, for example
+    /// one saved to a file. The response is parsed one entry at a time using
+    /// [`ModelEntries`](smtlib_lowlevel::ModelEntries), such that the text of
+    /// the whole response is never in memory at once.
+    ///
+    /// Note that this only applies to reading models using this function, as
+    /// [`Solver::get_model`] reads the whole response of the solver before
+    /// parsing it.
+    ///
+    /// ```
+    /// # use smtlib::Model;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let model = Model::from_reader("((define-fun x () Int 3))".as_bytes())?;
+    /// assert_eq!(model.get("x").unwrap().to_string(), "3");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader(reader: impl std::io::BufRead) -> Result<Self, Error> {
+        Ok(Self {
+            values: smtlib_lowlevel::ModelEntries::new(reader)
+                .map(|res| Self::entry(res?))
+                .collect::<Result<_, Error>>()?,
+            labels: HashMap::new(),
+        })
+    }
     /// Extract the value of a constant. Returns `None` if the value was not
     /// part of the model, which occurs if the constant was not part of any
     /// expression asserted.
     ///
+    /// The value is a term of the same sort as the constant, which can be
+    /// converted to a Rust value using `TryFrom`, for example [`bool`] for
+    /// [`Bool`], [`i64`] for [`Int`] and [`BitVec`], and [`f64`] for
+    /// [`Real`]. Integers too large for an [`i64`] can be converted to a
+    /// [`BigInt`](num_bigint::BigInt).
+    ///
     /// ```
     /// # use smtlib::{Int, Sort};
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -186,6 +651,8 @@ impl Model {
     /// let x = Int::from_name("x");
     /// solver.assert(x._eq(12))?;
     /// let model = solver.check_sat_with_model()?.expect_sat()?;
+    /// let x: i64 = model.eval(x).unwrap().try_into()?;
+    /// assert_eq!(x, 12);
     /// # Ok(())
     /// # }
     /// ```
@@ -193,6 +660,55 @@ impl Model {
     where
         T::Inner: From<ast::Term>,
     {
-        Some(self.values.get(x.name().trim_matches('|'))?.clone().into())
+        Some(self.get(x.name())?.clone().into())
+    }
+    /// Extract the value of the term labeled by `label` using
+    /// [`Sort::labeled`]. Returns `None` if the value is not part of the
+    /// model, which occurs if the labeled term was not part of any assertion
+    /// when the model was produced by [`Solver::get_model`].
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// let (label, doubled) = (x * 2).labeled();
+    /// solver.assert(doubled._eq(8))?;
+    /// let model = solver.check_sat_with_model()?.expect_sat()?;
+    /// assert_eq!(i64::try_from(model.eval_label(&label).unwrap())?, 8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_label<T: Sort>(&self, label: &Label<T>) -> Option<T::Inner>
+    where
+        T::Inner: From<ast::Term>,
+    {
+        Some(self.labels.get(&label.name())?.clone().into())
+    }
+    /// Returns the value of the constant named `name`, if it is part of the
+    /// model.
+    pub fn get(&self, name: &str) -> Option<&ast::Term> {
+        Some(&self.values.get(name.trim_matches('|'))?.1)
+    }
+    /// Iterates over the name, sort and value of every constant in the model,
+    /// in no particular order.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// # let x = Int::from_name("x");
+    /// # solver.assert(x._eq(12))?;
+    /// let model = solver.check_sat_with_model()?.expect_sat()?;
+    /// for (name, sort, value) in model.iter() {
+    ///     println!("{name}: {sort} = {value}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ast::Sort, &ast::Term)> {
+        self.values
+            .iter()
+            .map(|(name, (sort, value))| (name.as_str(), sort, value))
     }
 }
@@ -1,3 +1,97 @@
 #![allow(clippy::all)]
 
+use smtlib_lowlevel::ast::{Identifier, QualIdentifier, Sort, SpecConstant, Term};
+
+use crate::Error;
+
 include!(concat!(env!("OUT_DIR"), "/logic.rs"));
+
+impl Logic {
+    /// Determines if the logic is quantifier-free, that is, if its name
+    /// starts with `QF_`, following the naming convention of SMT-LIB.
+    pub fn is_quantifier_free(&self) -> bool {
+        self.to_string().starts_with("QF_")
+    }
+    /// Determines if `theory` is part of the logic.
+    pub fn supports(&self, theory: Theory) -> bool {
+        theory == Theory::Core || self.theories().contains(&theory)
+    }
+}
+
+impl Logic {
+    /// Checks that `term` only uses features which are part of the logic,
+    /// that is, no quantifiers in a quantifier-free logic, and only sorts of
+    /// the theories of the logic. For [`Logic::Custom`] only quantifiers are
+    /// checked, as its theories are not known.
+    pub(crate) fn check(&self, term: &Term) -> Result<(), Error> {
+        match self.term_violation(term) {
+            Some(reason) => Err(Error::UnsupportedByLogic {
+                logic: self.to_string(),
+                term: term.to_string(),
+                reason,
+            }),
+            None => Ok(()),
+        }
+    }
+    fn term_violation(&self, term: &Term) -> Option<String> {
+        match term {
+            Term::SpecConstant(SpecConstant::Decimal(d))
+                if !self.supports(Theory::Reals) && !self.supports(Theory::Reals_Ints) =>
+            {
+                self.theories_known()
+                    .then(|| format!("uses the decimal {d}"))
+            }
+            Term::SpecConstant(_) => None,
+            Term::Identifier(q) => self.ident_violation(q),
+            Term::Application(q, args) => self
+                .ident_violation(q)
+                .or_else(|| args.iter().find_map(|arg| self.term_violation(arg))),
+            Term::Let(bindings, body) => bindings
+                .iter()
+                .find_map(|b| self.term_violation(&b.1))
+                .or_else(|| self.term_violation(body)),
+            Term::Forall(_, _) | Term::Exists(_, _) if self.is_quantifier_free() => {
+                Some("uses quantifiers".to_string())
+            }
+            Term::Forall(vars, body) | Term::Exists(vars, body) => vars
+                .iter()
+                .find_map(|v| self.sort_violation(&v.1))
+                .or_else(|| self.term_violation(body)),
+            Term::Match(t, cases) => self
+                .term_violation(t)
+                .or_else(|| cases.iter().find_map(|c| self.term_violation(&c.1))),
+            Term::Annotation(t, _) => self.term_violation(t),
+        }
+    }
+    fn ident_violation(&self, q: &QualIdentifier) -> Option<String> {
+        match q {
+            QualIdentifier::Identifier(_) => None,
+            QualIdentifier::Sorted(_, sort) => self.sort_violation(sort),
+        }
+    }
+    fn sort_violation(&self, sort: &Sort) -> Option<String> {
+        let (ident, params) = match sort {
+            Sort::Sort(ident) => (ident, &[][..]),
+            Sort::Parametric(ident, params) => (ident, &params[..]),
+        };
+        let (Identifier::Simple(name) | Identifier::Indexed(name, _)) = ident;
+        let theories: &[Theory] = match name.0.as_str() {
+            "Int" => &[Theory::Ints, Theory::Reals_Ints],
+            "Real" => &[Theory::Reals, Theory::Reals_Ints],
+            "BitVec" => &[Theory::FixedSizeBitVectors],
+            "Array" => &[Theory::ArraysEx],
+            "FiniteField" => &[Theory::FieldElements],
+            _ => &[],
+        };
+        if self.theories_known()
+            && !theories.is_empty()
+            && !theories.iter().any(|t| self.supports(*t))
+        {
+            return Some(format!("uses the sort {sort}"));
+        }
+        params.iter().find_map(|s| self.sort_violation(s))
+    }
+    fn theories_known(&self) -> bool {
+        !matches!(self, Logic::Custom(_))
+    }
+}
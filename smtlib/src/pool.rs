@@ -0,0 +1,225 @@
+//! Reusing solver processes across many independent problems.
+//!
+//! Spawning a solver is often far more expensive than solving a small
+//! problem. A [`SolverPool`] keeps solvers around once they are done, and
+//! [`reset`](Solver::reset)s them before handing them out again.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Condvar, Mutex},
+};
+
+use crate::{Backend, Error, Solver, SolverBuilder};
+
+type Spawn<B> = Box<dyn Fn() -> Result<B, smtlib_lowlevel::Error> + Send + Sync>;
+
+/// A pool of at most `max` solvers, which are spawned lazily and reused
+/// between calls to [`SolverPool::get`].
+///
+/// When a [`PooledSolver`] is dropped, the solver is reset and returned to the
+/// pool, rather than killing the underlying process. If resetting fails, the
+/// solver is discarded, and a new one is spawned the next time one is needed.
+///
+/// ```
+/// # use smtlib::{backend::Z3Binary, Int, SatResult, SolverBuilder, SolverPool, Sort};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let pool = SolverPool::new(SolverBuilder::new(), 4, || Z3Binary::new("z3"));
+/// for i in 0..10 {
+///     let mut solver = pool.get()?;
+///     let x = Int::from_name("x");
+///     solver.assert(x._eq(i))?;
+///     assert_eq!(solver.check_sat()?, SatResult::Sat);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SolverPool<B> {
+    builder: SolverBuilder,
+    spawn: Spawn<B>,
+    max: usize,
+    state: Mutex<PoolState<B>>,
+    returned: Condvar,
+}
+
+struct PoolState<B> {
+    idle: Vec<Solver<B>>,
+    live: usize,
+}
+
+impl<B: Backend> SolverPool<B> {
+    /// Construct a pool of at most `max` solvers, each configured by `builder`
+    /// and using a backend created by `spawn`. No solvers are spawned until
+    /// they are needed.
+    pub fn new<E>(
+        builder: SolverBuilder,
+        max: usize,
+        spawn: impl Fn() -> Result<B, E> + Send + Sync + 'static,
+    ) -> Self
+    where
+        E: Into<smtlib_lowlevel::Error>,
+    {
+        assert!(max > 0, "a solver pool must allow at least one solver");
+        Self {
+            builder,
+            spawn: Box::new(move || spawn().map_err(Into::into)),
+            max,
+            state: Mutex::new(PoolState {
+                idle: vec![],
+                live: 0,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+    /// Takes an idle solver from the pool, or spawns a new one if fewer than
+    /// `max` are alive. Otherwise, this blocks until another solver is
+    /// returned to the pool.
+    pub fn get(&self) -> Result<PooledSolver<'_, B>, Error> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(solver) = state.idle.pop() {
+                return Ok(PooledSolver {
+                    pool: self,
+                    solver: Some(solver),
+                });
+            }
+            if state.live < self.max {
+                state.live += 1;
+                drop(state);
+                return match self.spawn_solver() {
+                    Ok(solver) => Ok(PooledSolver {
+                        pool: self,
+                        solver: Some(solver),
+                    }),
+                    Err(e) => {
+                        self.discard();
+                        Err(e)
+                    }
+                };
+            }
+            state = self.returned.wait(state).unwrap();
+        }
+    }
+    /// The number of solvers currently alive, both idle and in use.
+    pub fn live(&self) -> usize {
+        self.state.lock().unwrap().live
+    }
+    fn spawn_solver(&self) -> Result<Solver<B>, Error> {
+        let backend = (self.spawn)()?;
+        self.builder.clone().build(backend)
+    }
+    fn discard(&self) {
+        self.state.lock().unwrap().live -= 1;
+        self.returned.notify_one();
+    }
+}
+
+/// A [`Solver`] borrowed from a [`SolverPool`], which is reset and returned to
+/// the pool when dropped.
+pub struct PooledSolver<'a, B: Backend> {
+    pool: &'a SolverPool<B>,
+    solver: Option<Solver<B>>,
+}
+
+impl<B: Backend> Deref for PooledSolver<'_, B> {
+    type Target = Solver<B>;
+
+    fn deref(&self) -> &Self::Target {
+        self.solver.as_ref().unwrap()
+    }
+}
+
+impl<B: Backend> DerefMut for PooledSolver<'_, B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.solver.as_mut().unwrap()
+    }
+}
+
+impl<B: Backend> Drop for PooledSolver<'_, B> {
+    fn drop(&mut self) {
+        let Some(mut solver) = self.solver.take() else {
+            return;
+        };
+        let reset = solver
+            .reset()
+            .and_then(|()| self.pool.builder.configure(&mut solver));
+        match reset {
+            Ok(()) => {
+                self.pool.state.lock().unwrap().idle.push(solver);
+                self.pool.returned.notify_one();
+            }
+            // NOTE: A solver which cannot be reset is in an unknown state, so
+            // rather than reusing it we let it be killed
+            Err(_) => self.pool.discard(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use smtlib_lowlevel::backend::{MockBackend, Z3Binary};
+
+    use crate::{terms::Sort, Int, SatResult, SolverBuilder};
+
+    use super::SolverPool;
+
+    #[test]
+    fn reuses_process() -> Result<(), Box<dyn std::error::Error>> {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let pool = SolverPool::new(SolverBuilder::new(), 2, {
+            let spawned = spawned.clone();
+            move || {
+                spawned.fetch_add(1, Ordering::SeqCst);
+                Z3Binary::new("z3")
+            }
+        });
+        assert_eq!(pool.live(), 0);
+
+        let x = Int::from_name("x");
+        {
+            let mut solver = pool.get()?;
+            solver.assert(x.gt(2) & x.lt(2))?;
+            assert_eq!(solver.check_sat()?, SatResult::Unsat);
+        }
+        {
+            // The assertions of the previous borrow are gone after the reset
+            let mut solver = pool.get()?;
+            solver.assert(x.gt(2))?;
+            assert_eq!(solver.check_sat()?, SatResult::Sat);
+        }
+
+        assert_eq!(spawned.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.live(), 1);
+
+        Ok(())
+    }
+    #[test]
+    fn recycled_transcript() -> Result<(), Box<dyn std::error::Error>> {
+        let builder = SolverBuilder::new().record_transcript(true).random_seed(42);
+        let pool = SolverPool::new(builder, 1, || {
+            Ok::<_, smtlib_lowlevel::Error>(MockBackend::scripted(["sat"]))
+        });
+
+        {
+            let mut solver = pool.get()?;
+            solver.assert(Int::from_name("x").gt(2))?;
+            assert_eq!(solver.check_sat()?, SatResult::Sat);
+        }
+        let solver = pool.get()?;
+        let transcript = solver.transcript();
+        assert!(!transcript.is_empty());
+        assert!(
+            transcript
+                .lines()
+                .all(|cmd| cmd.starts_with("(set-option ")),
+            "{transcript}"
+        );
+        assert!(transcript.ends_with("(set-option :random-seed 42)\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Solving the same problem with several backends at once, taking the answer
+//! of whichever finishes first.
+//!
+//! Different solvers excel at different problems, and it is rarely known up
+//! front which one is the fastest. [`solve_first`] runs every backend on its
+//! own thread and kills the rest as soon as one of them has an answer.
+
+use std::sync::mpsc;
+
+use crate::{Backend, Bool, Error, SatResult, Solver};
+
+/// Checks the satisfiability of the conjunction of `problem` with each of
+/// `backends` in parallel, returning the first definitive answer, that is,
+/// [`SatResult::Sat`] or [`SatResult::Unsat`].
+///
+/// Once an answer is found, the remaining backends are killed using
+/// [`Backend::kill_handle`]. Backends which do not provide a kill handle are
+/// left to finish on their own thread, and their result is discarded.
+///
+/// If no backend produces a definitive answer, [`SatResult::Unknown`] is
+/// returned if any backend answered `unknown` (or if `backends` is empty), and
+/// otherwise the error of the last backend to fail.
+///
+/// ```
+/// # use smtlib::{backend::{Cvc5Binary, Z3Binary}, Int, Sort};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let x = Int::from_name("x");
+/// let res = smtlib::race::solve_first(
+///     &[x.gt(2), x.lt(4)],
+///     vec![
+///         Box::new(Z3Binary::new("z3")?),
+///         Box::new(Cvc5Binary::new("cvc5")?),
+///     ],
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn solve_first(
+    problem: &[Bool],
+    backends: Vec<Box<dyn Backend + Send>>,
+) -> Result<SatResult, Error> {
+    let (tx, rx) = mpsc::channel();
+    let mut handles = vec![];
+
+    for backend in backends {
+        handles.extend(backend.kill_handle());
+        let problem = problem.to_vec();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let res = (|| {
+                let mut solver = Solver::new(backend, false)?;
+                for b in problem {
+                    solver.assert(b)?;
+                }
+                solver.check_sat()
+            })();
+            // NOTE: The receiver is gone if another backend already answered
+            let _ = tx.send(res);
+        });
+    }
+    drop(tx);
+
+    let mut answer = None;
+    let mut unknown = false;
+    let mut error = None;
+    for res in rx.iter() {
+        match res {
+            Ok(res @ (SatResult::Sat | SatResult::Unsat)) => {
+                answer = Some(res);
+                break;
+            }
+            Ok(SatResult::Unknown) => unknown = true,
+            Err(e) => error = Some(e),
+        }
+    }
+
+    for handle in handles {
+        // NOTE: Errors here only indicate that the process already exited
+        let _ = handle.kill();
+    }
+
+    match (answer, error) {
+        (Some(res), _) => Ok(res),
+        (None, Some(e)) if !unknown => Err(e),
+        (None, _) => Ok(SatResult::Unknown),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{terms::Sort, Int, SatResult};
+
+    use super::solve_first;
+
+    #[test]
+    fn race_two_z3() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+
+        let res = solve_first(
+            &[x.gt(2), x.lt(4)],
+            vec![
+                Box::new(Z3Binary::new("z3")?),
+                Box::new(Z3Binary::new("z3")?),
+            ],
+        )?;
+        assert!(matches!(res, SatResult::Sat));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Solving purely propositional problems given as clauses of DIMACS-style
+//! literals.
+//!
+//! For problems which only consist of clauses over boolean variables, building
+//! terms is unnecessary ceremony. A [`SatSolver`] accepts clauses of non-zero
+//! integers, where `i` is the `i`'th variable and `-i` its negation, and
+//! produces the satisfying assignment as a vector of booleans.
+
+use crate::{terms::fun, Backend, Bool, Error, SatResult, SatResultWithModel, Solver, Sort};
+
+/// A solver for propositional problems in conjunctive normal form, where the
+/// variable `i` is the boolean constant `p{i}` of the underlying [`Solver`].
+///
+/// ```
+/// # use smtlib::{backend::Z3Binary, SatSolver};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut solver = SatSolver::new(Z3Binary::new("z3")?)?;
+/// solver.add_clauses(vec![vec![1, 2], vec![-1], vec![-2, 3]])?;
+/// assert_eq!(solver.solve()?, Some(vec![false, true, true]));
+///
+/// solver.add_clause(&[-3])?;
+/// assert_eq!(solver.solve()?, None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SatSolver<B> {
+    solver: Solver<B>,
+    vars: usize,
+}
+
+impl<B: Backend> SatSolver<B> {
+    /// Construct a new SAT solver using the given `backend`.
+    pub fn new(backend: B) -> Result<Self, Error> {
+        Ok(Self::from_solver(Solver::new(backend, false)?))
+    }
+    /// Use an already configured `solver`. The variables are constants named
+    /// `p1`, `p2`, and so on, which should not otherwise be used in the
+    /// solver.
+    pub fn from_solver(solver: Solver<B>) -> Self {
+        SatSolver { solver, vars: 0 }
+    }
+    /// The underlying solver.
+    pub fn solver(&mut self) -> &mut Solver<B> {
+        &mut self.solver
+    }
+    /// The number of variables, that is, the largest variable used in any
+    /// clause so far.
+    pub fn num_vars(&self) -> usize {
+        self.vars
+    }
+    /// The constant for the variable `var`, which is `p{var}`.
+    pub fn var(var: usize) -> Bool {
+        Bool::from_name(format!("p{var}")).into()
+    }
+    /// Asserts the disjunction of the `clause`, where a positive literal `i`
+    /// is the variable `i`, and a negative literal `-i` is its negation. The
+    /// empty clause is `false`.
+    ///
+    /// Panics if any of the literals is `0`, which is not a variable.
+    pub fn add_clause(&mut self, clause: &[i32]) -> Result<(), Error> {
+        let mut literals: Vec<_> = clause
+            .iter()
+            .map(|&lit| {
+                assert!(lit != 0, "0 is not a valid literal");
+                let var = lit.unsigned_abs() as usize;
+                self.vars = self.vars.max(var);
+                if lit > 0 {
+                    Self::var(var)
+                } else {
+                    !Self::var(var)
+                }
+            })
+            .collect();
+        let clause = match literals.len() {
+            0 => Bool::from(false),
+            1 => literals.pop().unwrap(),
+            _ => fun("or", literals.into_iter().map(Into::into).collect()).into(),
+        };
+        self.solver.assert(clause)
+    }
+    /// Asserts each of `clauses`. See [`SatSolver::add_clause`].
+    pub fn add_clauses(
+        &mut self,
+        clauses: impl IntoIterator<Item = impl AsRef<[i32]>>,
+    ) -> Result<(), Error> {
+        for clause in clauses {
+            self.add_clause(clause.as_ref())?;
+        }
+        Ok(())
+    }
+    /// Checks if the clauses are satisfiable, returning the value of each
+    /// variable `1..=n` at index `0..n` if so, and `None` if they are not.
+    ///
+    /// Returns an error if the solver answers `unknown`.
+    pub fn solve(&mut self) -> Result<Option<Vec<bool>>, Error> {
+        let model = match self.solver.check_sat_with_model()? {
+            SatResultWithModel::Sat(model) => model,
+            SatResultWithModel::Unsat => return Ok(None),
+            SatResultWithModel::Unknown => {
+                return Err(Error::UnexpectedSatResult {
+                    expected: SatResult::Sat,
+                    actual: SatResult::Unknown,
+                })
+            }
+        };
+        (1..=self.vars)
+            .map(|var| match model.get(&format!("p{var}")) {
+                // NOTE: Variables which do not occur in any clause are not
+                // part of the model, as they can take either value
+                Some(value) => bool::try_from(Bool::from(value.clone())),
+                None => Ok(false),
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use super::SatSolver;
+
+    #[test]
+    fn three_clauses() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SatSolver::new(Z3Binary::new("z3")?)?;
+
+        let clauses = vec![vec![1, -2], vec![2, 3], vec![-1, -3]];
+        solver.add_clauses(&clauses)?;
+        let assignment = solver.solve()?.expect("the clauses are satisfiable");
+        assert_eq!(assignment.len(), 3);
+        for clause in &clauses {
+            assert!(clause
+                .iter()
+                .any(|&lit| assignment[lit.unsigned_abs() as usize - 1] == (lit > 0)));
+        }
+
+        solver.add_clauses([[1], [3]])?;
+        assert_eq!(solver.solve()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unused_variables() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SatSolver::new(Z3Binary::new("z3")?)?;
+
+        solver.add_clause(&[-4])?;
+        assert_eq!(solver.num_vars(), 4);
+        assert_eq!(solver.solve()?, Some(vec![false; 4]));
+        solver.add_clause(&[])?;
+        assert_eq!(solver.solve()?, None);
+
+        Ok(())
+    }
+}
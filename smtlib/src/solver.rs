@@ -1,15 +1,28 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
 
 use smtlib_lowlevel::{
-    ast::{self, Identifier, QualIdentifier},
+    ast::{self, Identifier, QualIdentifier, SortedVar},
     backend,
-    lexicon::Symbol,
+    lexicon::{Keyword, Numeral, Symbol},
     Driver,
 };
 
 use num_bigint::BigUint;
 
-use crate::{Bool, Error, Logic, Model, SatResult, SatResultWithModel};
+use crate::{
+    terms::{fun, qual_ident, Const, Dynamic, Fun, Label, Sort, LABEL_PREFIX},
+    theories::{
+        datatypes::Datatype, fieldelements::FieldElement, sets, tuples,
+        uninterpreted::UninterpretedSort,
+    },
+    Bool, Error, Int, Logic, Model, SatResult, SatResultWithModel,
+};
 
 /// The [`Solver`] type is the primary entrypoint to interaction with the
 /// solver. Checking for validity of a set of assertions requires:
@@ -41,6 +54,40 @@ use crate::{Bool, Error, Logic, Model, SatResult, SatResultWithModel};
 pub struct Solver<B> {
     pub driver: Driver<B>,
     decls: HashMap<Identifier, ast::Sort>,
+    tuples: HashSet<String>,
+    labels: Vec<Symbol>,
+    last_result: Option<SatResult>,
+    logic: Option<Logic>,
+    validate_logic: bool,
+    /// Incremented whenever the state of the solver changes, such as by
+    /// assertions, declarations, and checking satisfiability.
+    generation: u64,
+    /// The last model produced and the generation it was produced at, if
+    /// models are cached.
+    cached_model: Option<(u64, Model)>,
+    cache_models: bool,
+    /// Whether the transcript contains every command changing the state of
+    /// the solver since it was constructed or last reset, as required by
+    /// [`Solver::fork`].
+    transcript_complete: bool,
+    /// Whether `:produce-proofs` is enabled, as required by
+    /// [`Solver::get_proof`].
+    produce_proofs: bool,
+    /// Whether `:produce-unsat-assumptions` is enabled, as required by
+    /// [`Solver::get_unsat_assumptions`].
+    produce_unsat_assumptions: bool,
+    /// The declarations as they were when each scope of the assertion stack
+    /// was pushed using [`Solver::push`], innermost last.
+    scopes: Vec<Scope>,
+}
+
+/// The declarations of a [`Solver`], which are restored when popping the
+/// scope of the assertion stack they were saved at.
+#[derive(Debug, Clone)]
+struct Scope {
+    decls: HashMap<Identifier, ast::Sort>,
+    tuples: HashSet<String>,
+    labels: Vec<Symbol>,
 }
 
 impl<B> Solver<B>
@@ -52,23 +99,179 @@ where
     /// The read more about which backends are available, check out the
     /// documentation of the [`backend`] module.
     pub fn new(backend: B, verbose: bool) -> Result<Self, Error> {
+        let mut driver = Driver::new(backend, verbose)?;
+        Self::set_default_options(&mut driver)?;
+
         Ok(Self {
-            driver: Driver::new(backend, verbose)?,
+            driver,
             decls: Default::default(),
+            tuples: Default::default(),
+            labels: Default::default(),
+            last_result: None,
+            logic: None,
+            validate_logic: false,
+            generation: 0,
+            cached_model: None,
+            cache_models: false,
+            transcript_complete: false,
+            produce_proofs: false,
+            produce_unsat_assumptions: false,
+            scopes: vec![],
         })
     }
+    fn set_default_options(driver: &mut Driver<B>) -> Result<(), Error> {
+        // Required by some solvers for `(get-assertions)`
+        driver.exec(&ast::Command::SetOption(ast::Option::ProduceAssertions(
+            true,
+        )))?;
+        Ok(())
+    }
+    /// Resets the solver to the state it was in right after [`Solver::new`],
+    /// using the `reset` command. This removes all assertions, declarations,
+    /// and the logic, as well as any options set since. The
+    /// [transcript](Solver::transcript) is cleared, after which it only
+    /// contains the options sent again following the reset.
+    ///
+    /// This is considerably cheaper than spawning a new solver, and is what
+    /// [`SolverPool`](crate::SolverPool) uses to reuse solvers.
+    pub fn reset(&mut self) -> Result<(), Error> {
+        let cmd = ast::Command::Reset;
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => {}
+            ast::GeneralResponse::Error(e) => return Err(Error::Smt(e.into(), cmd.to_string())),
+            res => return Err(Error::unexpected_response(&cmd, res)),
+        }
+        self.driver.clear_transcript();
+        self.transcript_complete = self.driver.records_transcript();
+        // NOTE: `(reset)` also resets `:print-success`, which the driver
+        // relies on to know when a command is done
+        self.driver
+            .exec(&ast::Command::SetOption(ast::Option::PrintSuccess(true)))?;
+        Self::set_default_options(&mut self.driver)?;
+        self.decls.clear();
+        self.tuples.clear();
+        self.labels.clear();
+        self.last_result = None;
+        self.changed();
+        self.logic = None;
+        self.produce_proofs = false;
+        self.produce_unsat_assumptions = false;
+        self.scopes.clear();
+        Ok(())
+    }
+    /// Pushes `n` new scopes onto the assertion stack using `(push n)`. The
+    /// assertions and declarations made from now on are removed again when
+    /// the scopes are popped using [`Solver::pop`].
+    ///
+    /// ```
+    /// # use smtlib::{Int, SatResult, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// solver.assert(x.gt(2))?;
+    /// solver.push(1)?;
+    /// solver.assert(x.lt(2))?;
+    /// assert_eq!(solver.check_sat()?, SatResult::Unsat);
+    /// solver.pop(1)?;
+    /// assert_eq!(solver.check_sat()?, SatResult::Sat);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn push(&mut self, n: usize) -> Result<(), Error> {
+        let cmd = ast::Command::Push(Numeral(n.to_string()));
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => {
+                let scope = Scope {
+                    decls: self.decls.clone(),
+                    tuples: self.tuples.clone(),
+                    labels: self.labels.clone(),
+                };
+                self.scopes.extend(vec![scope; n]);
+                self.last_result = None;
+                Ok(())
+            }
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Pops `n` scopes off the assertion stack using `(pop n)`, removing the
+    /// assertions and declarations made since they were pushed using
+    /// [`Solver::push`].
+    pub fn pop(&mut self, n: usize) -> Result<(), Error> {
+        let cmd = ast::Command::Pop(Numeral(n.to_string()));
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => {
+                let depth = self.scopes.len().saturating_sub(n);
+                if let Some(scope) = self.scopes.drain(depth..).next() {
+                    self.decls = scope.decls;
+                    self.tuples = scope.tuples;
+                    self.labels = scope.labels;
+                }
+                self.last_result = None;
+                Ok(())
+            }
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Sets `option` using the `set-option` command. Most options must be set
+    /// before any assertions are made, in which case consider using
+    /// [`SolverBuilder`] instead.
+    ///
+    /// ```
+    /// # use smtlib::backend::Z3Binary;
+    /// # use smtlib_lowlevel::{ast, lexicon::Numeral};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(Z3Binary::new("z3")?, false)?;
+    /// solver.set_option(ast::Option::RandomSeed(Numeral("42".to_string())))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_option(&mut self, option: ast::Option) -> Result<(), Error> {
+        let cmd = ast::Command::SetOption(option);
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => {
+                self.record_option(&cmd);
+                Ok(())
+            }
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Records whether the options required by [`Solver::get_proof`] and
+    /// [`Solver::get_unsat_assumptions`] are enabled by `cmd`, once the solver
+    /// has accepted it.
+    fn record_option(&mut self, cmd: &ast::Command) {
+        match cmd {
+            ast::Command::SetOption(ast::Option::ProduceProofs(enable)) => {
+                self.produce_proofs = *enable;
+            }
+            ast::Command::SetOption(ast::Option::ProduceUnsatAssumptions(enable)) => {
+                self.produce_unsat_assumptions = *enable;
+            }
+            _ => {}
+        }
+    }
     /// Explicitly sets the logic for the solver. For some backends this is not
     /// required, as they will infer what ever logic fits the current program.
     ///
     /// To read more about logics read the documentation of [`Logic`].
     pub fn set_logic(&mut self, logic: Logic) -> Result<(), Error> {
         let cmd = ast::Command::SetLogic(Symbol(logic.to_string()));
-        println!("Setting logic to {logic}");
+        self.changed();
         match self.driver.exec(&cmd)? {
-            ast::GeneralResponse::Success => Ok(()),
-            ast::GeneralResponse::SpecificSuccessResponse(_) => panic!("Failed to set logic"),
-            ast::GeneralResponse::Unsupported => panic!("Failed to set logic - Unsupported"),
-            ast::GeneralResponse::Error(_) => panic!("Failed to set logic - Err"),
+            ast::GeneralResponse::Success => {
+                self.logic = Some(logic);
+                Ok(())
+            }
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
 
@@ -77,15 +280,8 @@ where
     ///
     /// To read more about logics read the documentation of [`Logic`].
     pub fn set_field_order(&mut self, prime: &BigUint) -> Result<(), Error> {
-        let ff_sort = ast::Sort::Sort(Identifier::Simple(Symbol(format!("(_ FiniteField {prime})").into())));
-        let sort_command = ast::Command::DefineSort(Symbol("F".into()), vec![], ff_sort);
-        
-        match self.driver.exec(&sort_command)? {
-            ast::GeneralResponse::Success => Ok(()),
-            ast::GeneralResponse::SpecificSuccessResponse(_) => panic!("Failed to set field order"),
-            ast::GeneralResponse::Unsupported => panic!("Failed to set logic - Unsupported"),
-            ast::GeneralResponse::Error(_) => panic!("Failed to set logic - Err"),
-        }
+        let ff_sort = FieldElement::field_sort(prime);
+        self.define_sort("F", vec![], ff_sort)
     }
 
     /// Adds the constraint of `b` as an assertion to the solver. To check for
@@ -93,30 +289,436 @@ where
     /// [`Solver::check_sat_with_model`].
     pub fn assert(&mut self, b: Bool) -> Result<(), Error> {
         let term = ast::Term::from(b);
-        for q in term.all_consts() {
-            match q {
-                QualIdentifier::Identifier(_) => {}
-                QualIdentifier::Sorted(i, s) => match self.decls.entry(i.clone()) {
-                    Entry::Occupied(stored) => assert_eq!(s, stored.get()),
-                    Entry::Vacant(v) => {
-                        v.insert(s.clone());
-                        match i {
-                            Identifier::Simple(sym) => {
-                                // Want this to be declare-fun
-                                self.driver
-                                    .exec(&ast::Command::DeclareConst(sym.clone(), s.clone()))?;
-                            }
-                            Identifier::Indexed(_, _) => todo!(),
-                        }
-                    }
-                },
+        self.validate(&term)?;
+        self.declare_all_consts(&term, &[])?;
+        self.last_result = None;
+        self.changed();
+        let cmd = ast::Command::Assert(term);
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => {
+                self.record_labels(&cmd);
+                Ok(())
             }
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
-        let cmd = ast::Command::Assert(term);
+    }
+    /// Adds the constraints of all of `bs` as assertions to the solver. This is
+    /// equivalent to calling [`Solver::assert`] on each of them, but the
+    /// constants of all the terms are declared up front, and all of the
+    /// commands are sent to the solver as a single
+    /// [batch](smtlib_lowlevel::backend::Backend::exec_batch).
+    pub fn assert_all(&mut self, bs: impl IntoIterator<Item = Bool>) -> Result<(), Error> {
+        let terms: Vec<ast::Term> = bs.into_iter().map(ast::Term::from).collect();
+        for term in &terms {
+            self.validate(term)?;
+        }
+        // NOTE: The declarations are made on copies, such that nothing is
+        // recorded if any of the terms uses a constant with the wrong sort
+        let (mut decls, mut tuples) = (self.decls.clone(), self.tuples.clone());
+        let mut cmds = vec![];
+        for term in &terms {
+            cmds.extend(declarations(&mut decls, &mut tuples, term, &[])?);
+        }
+        (self.decls, self.tuples) = (decls, tuples);
+        cmds.extend(terms.into_iter().map(ast::Command::Assert));
+        self.last_result = None;
+        self.changed();
+        for (cmd, res) in cmds.iter().zip(self.driver.exec_batch(&cmds)?) {
+            match res {
+                ast::GeneralResponse::Success => self.record_labels(cmd),
+                ast::GeneralResponse::Error(e) => {
+                    return Err(Error::Smt(e.into(), cmd.to_string()))
+                }
+                ast::GeneralResponse::Unsupported => {
+                    return Err(Error::Unsupported(cmd.to_string()))
+                }
+                res => return Err(Error::unexpected_response(cmd, res)),
+            }
+        }
+        Ok(())
+    }
+    /// Parses `script` as SMT-LIB commands and executes each of them,
+    /// returning the response to each command in order.
+    ///
+    /// Constants declared by the script using `declare-const`, or using
+    /// `declare-fun` without arguments, are remembered, such that terms
+    /// asserted afterwards refer to them rather than declaring them again.
+    /// Likewise a logic set by the script using `set-logic` is remembered, as
+    /// if set using [`Solver::set_logic`], as are the options set using
+    /// `set-option`. The commands `push`, `pop`, and `reset` are executed
+    /// using [`Solver::push`], [`Solver::pop`], and [`Solver::reset`], such
+    /// that the remembered declarations follow along.
+    ///
+    /// Scripts containing `(set-option :print-success false)`, which the
+    /// solver relies on to know when a command is done, or
+    /// `(reset-assertions)` are rejected with [`Error::Unsupported`] before
+    /// any of their commands are executed.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// solver.load_script("(declare-const x Int)\n(assert (> x 0))")?;
+    /// solver.assert(Int::from_name("x").lt(10))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_script(&mut self, script: &str) -> Result<Vec<ast::GeneralResponse>, Error> {
+        let script = ast::Script::parse(script).map_err(smtlib_lowlevel::Error::from)?;
+        if let Some(cmd) = script.0.iter().find(|cmd| {
+            matches!(
+                cmd,
+                ast::Command::SetOption(ast::Option::PrintSuccess(false))
+                    | ast::Command::ResetAssertions
+            )
+        }) {
+            return Err(Error::Unsupported(format!("{cmd} in a script")));
+        }
+        let scopes = |n: &Numeral| {
+            n.0.parse::<usize>()
+                .map_err(|_| Error::Unsupported(format!("{n} scopes")))
+        };
+        self.last_result = None;
+        self.changed();
+        let mut responses = vec![];
+        let mut batch = vec![];
+        for cmd in script.0 {
+            if !matches!(
+                cmd,
+                ast::Command::Push(_) | ast::Command::Pop(_) | ast::Command::Reset
+            ) {
+                batch.push(cmd);
+                continue;
+            }
+            responses.extend(self.exec_script(std::mem::take(&mut batch))?);
+            match cmd {
+                ast::Command::Push(n) => self.push(scopes(&n)?)?,
+                ast::Command::Pop(n) => self.pop(scopes(&n)?)?,
+                _ => self.reset()?,
+            }
+            responses.push(ast::GeneralResponse::Success);
+        }
+        responses.extend(self.exec_script(batch)?);
+        Ok(responses)
+    }
+    /// Executes `cmds` of a script loaded by [`Solver::load_script`] as a
+    /// single batch, remembering the declarations, logic, and options set by
+    /// the commands which succeed.
+    fn exec_script(&mut self, cmds: Vec<ast::Command>) -> Result<Vec<ast::GeneralResponse>, Error> {
+        if cmds.is_empty() {
+            return Ok(vec![]);
+        }
+        let responses = self.driver.exec_batch(&cmds)?;
+        for (cmd, res) in cmds.into_iter().zip(&responses) {
+            if *res != ast::GeneralResponse::Success {
+                continue;
+            }
+            match cmd {
+                ast::Command::DeclareConst(sym, sort) => {
+                    self.decls.insert(Identifier::Simple(sym), sort);
+                }
+                ast::Command::DeclareFun(sym, args, sort) if args.is_empty() => {
+                    self.decls.insert(Identifier::Simple(sym), sort);
+                }
+                ast::Command::SetLogic(Symbol(name)) => self.logic = Some(Logic::from_name(&name)),
+                cmd => self.record_option(&cmd),
+            }
+        }
+        Ok(responses)
+    }
+    /// Checks that `term` is allowed by the logic of the solver, if any, and
+    /// if enabled using [`SolverBuilder::validate_logic`].
+    fn validate(&self, term: &ast::Term) -> Result<(), Error> {
+        match &self.logic {
+            Some(logic) if self.validate_logic => logic.check(term),
+            _ => Ok(()),
+        }
+    }
+    /// Records that the state of the solver changes, such that a cached model
+    /// is no longer valid.
+    fn changed(&mut self) {
+        self.generation += 1;
+    }
+    /// Declares all of the constants in `term` which has not already been
+    /// declared, except for those bound by `bound`.
+    fn declare_all_consts(&mut self, term: &ast::Term, bound: &[SortedVar]) -> Result<(), Error> {
+        for cmd in declarations(&mut self.decls, &mut self.tuples, term, bound)? {
+            self.changed();
+            self.driver.exec(&cmd)?;
+        }
+        Ok(())
+    }
+    /// Records the labels of the terms asserted by `cmd`. This must only be
+    /// done once the solver has accepted the assertion, as querying the
+    /// value of a label it does not know is an error.
+    fn record_labels(&mut self, cmd: &ast::Command) {
+        if let ast::Command::Assert(term) = cmd {
+            labels(term, &mut self.labels);
+        }
+    }
+    /// Declares the tuple datatypes used in `sorts` which have not already
+    /// been declared.
+    fn declare_tuples<'a>(
+        &mut self,
+        sorts: impl IntoIterator<Item = &'a ast::Sort>,
+    ) -> Result<(), Error> {
+        for cmd in tuple_declarations(&mut self.tuples, sorts) {
+            self.changed();
+            self.driver.exec(&cmd)?;
+        }
+        Ok(())
+    }
+    /// Defines a function named `name` taking `params` as arguments, and
+    /// returning `body` of sort `ret`. The returned [`Fun`] can be applied
+    /// in later terms using [`Fun::call`].
+    ///
+    /// ```
+    /// # use smtlib::{terms::{QuantifierVars, Sort}, Int};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// let double = solver.define_fun::<Int>("double", x.clone().into_vars(), Int::sort(), x * 2)?;
+    /// solver.assert(double.call([Int::from(3).into()])._eq(6))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn define_fun<T>(
+        &mut self,
+        name: &str,
+        params: Vec<SortedVar>,
+        ret: ast::Sort,
+        body: impl Into<ast::Term>,
+    ) -> Result<Fun<T>, Error> {
+        let body = body.into();
+        self.declare_tuples(params.iter().map(|var| &var.1).chain([&ret]))?;
+        self.declare_all_consts(&body, &params)?;
+        let cmd = ast::Command::DefineFun(ast::FunctionDef(
+            Symbol(name.to_string()),
+            params,
+            ret,
+            body,
+        ));
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(Fun(name.into(), PhantomData)),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Declares a constant named `name` of the sort of `T` right away, rather
+    /// than when it first occurs in an assertion. This allows for example
+    /// retrieving its value using [`Solver::get_value`], even if it is never
+    /// constrained.
+    ///
+    /// Declaring a constant which is already declared with the same sort
+    /// does nothing, while declaring it with a different sort is an error.
+    ///
+    /// ```
+    /// # use smtlib::Int;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = solver.declare_const::<Int>("x")?;
+    /// solver.assert(x.gt(2))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn declare_const<T: Sort + From<ast::Term>>(
+        &mut self,
+        name: &str,
+    ) -> Result<Const<T>, Error> {
+        let c = T::from_name(name);
+        let sym = Symbol(c.name().to_string());
+        let sort = T::sort();
+        match self.decls.get(&Identifier::Simple(sym.clone())) {
+            Some(stored) if *stored == sort => return Ok(c),
+            Some(stored) => {
+                return Err(Error::SortMismatch {
+                    expected: sort.to_string(),
+                    actual: stored.to_string(),
+                })
+            }
+            None => {}
+        }
+        self.declare_tuples([&sort])?;
+        let cmd = ast::Command::DeclareConst(sym.clone(), sort.clone());
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            // NOTE: The constant is only remembered once the solver has
+            // accepted it, such that a rejected declaration is retried
+            ast::GeneralResponse::Success => {
+                self.decls.insert(Identifier::Simple(sym), sort);
+                Ok(c)
+            }
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Declares an uninterpreted function named `name` taking arguments of
+    /// sorts `args`, and returning a value of sort `ret`. The returned [`Fun`]
+    /// can be applied in later terms using [`Fun::call`].
+    ///
+    /// ```
+    /// # use smtlib::{terms::Sort, Int};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let f = solver.declare_fun::<Int>("f", vec![Int::sort()], Int::sort())?;
+    /// solver.assert(f.call([Int::from(0).into()])._eq(1))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn declare_fun<T>(
+        &mut self,
+        name: &str,
+        args: Vec<ast::Sort>,
+        ret: ast::Sort,
+    ) -> Result<Fun<T>, Error> {
+        self.declare_tuples(args.iter().chain([&ret]))?;
+        let cmd = ast::Command::DeclareFun(Symbol(name.to_string()), args, ret);
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(Fun(name.into(), PhantomData)),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Adds `b` as a soft constraint with the given `weight`, which the solver
+    /// tries to satisfy, minimizing the total weight of the violated soft
+    /// constraints. See [`Solver::check_sat_with_objectives`].
+    pub fn assert_soft(&mut self, b: Bool, weight: u64) -> Result<(), Error> {
+        let term = ast::Term::from(b);
+        self.declare_all_consts(&term, &[])?;
+        self.exec_objective(ast::Command::AssertSoft(term, Numeral(weight.to_string())))
+    }
+    /// Adds the objective of maximizing `t`. See
+    /// [`Solver::check_sat_with_objectives`].
+    pub fn maximize(&mut self, t: Int) -> Result<(), Error> {
+        let term = ast::Term::from(t);
+        self.declare_all_consts(&term, &[])?;
+        self.exec_objective(ast::Command::Maximize(term))
+    }
+    /// Adds the objective of minimizing `t`. See
+    /// [`Solver::check_sat_with_objectives`].
+    pub fn minimize(&mut self, t: Int) -> Result<(), Error> {
+        let term = ast::Term::from(t);
+        self.declare_all_consts(&term, &[])?;
+        self.exec_objective(ast::Command::Minimize(term))
+    }
+    fn exec_objective(&mut self, cmd: ast::Command) -> Result<(), Error> {
+        self.last_result = None;
+        self.changed();
         match self.driver.exec(&cmd)? {
             ast::GeneralResponse::Success => Ok(()),
-            ast::GeneralResponse::Error(e) => Err(Error::Smt(e, cmd.to_string())),
-            _ => todo!(),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Checks for satisfiability like [`Solver::check_sat`], and in case of
+    /// `sat` retrieves the optimal value of each of the objectives added
+    /// using [`Solver::minimize`], [`Solver::maximize`], and
+    /// [`Solver::assert_soft`], as reported by `(get-objectives)`.
+    ///
+    /// ```
+    /// # use smtlib::{terms::Sort, Int};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// solver.assert(x.ge(3))?;
+    /// solver.minimize(x.into())?;
+    /// let (_, objectives) = solver.check_sat_with_objectives()?;
+    /// assert_eq!(i64::try_from(objectives[0].clone())?, 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// > **NOTE:** Optimization is not part of the SMT-LIB standard, but is
+    /// > supported by for example z3.
+    pub fn check_sat_with_objectives(&mut self) -> Result<(SatResult, Vec<Int>), Error> {
+        match self.check_sat()? {
+            SatResult::Sat => {}
+            res => return Ok((res, vec![])),
+        }
+        let cmd = ast::Command::GetObjectives;
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetObjectivesResponse(res),
+            ) => Ok((
+                SatResult::Sat,
+                res.0
+                    .into_iter()
+                    .map(|ast::ValuationPair(_, value)| value.into())
+                    .collect(),
+            )),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Declares the algebraic datatype described by `datatype`, after which
+    /// its constructors, selectors, and constants of its sort can be used in
+    /// assertions.
+    ///
+    /// To read more about datatypes read the documentation of [`Datatype`].
+    pub fn declare_datatype(&mut self, datatype: &Datatype) -> Result<(), Error> {
+        let cmd = datatype.declaration();
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(()),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Declares an uninterpreted sort named `name` taking `arity` sort
+    /// parameters, returning a handle for constructing constants of the sort.
+    ///
+    /// To read more about uninterpreted sorts read the documentation of
+    /// [`UninterpretedSort`].
+    pub fn declare_sort(&mut self, name: &str, arity: usize) -> Result<UninterpretedSort, Error> {
+        let sort = UninterpretedSort::new(name, arity);
+        let cmd = sort.declaration();
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(sort),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Defines `name` as an alias of the sort `definition`, in which each of
+    /// `params` is a sort parameter which is substituted when the alias is
+    /// applied, such as `(define-sort Pair (X) (Tuple X X))`.
+    ///
+    /// Constants of the alias can be constructed using
+    /// [`Dynamic::from_name_with_sort`].
+    ///
+    /// ```
+    /// # use smtlib::{terms::{Dynamic, Sort}, Int, SatResult};
+    /// # use smtlib_lowlevel::{ast, lexicon::Symbol};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// solver.define_sort("Id", vec![], Int::sort())?;
+    /// let id = ast::Sort::Sort(ast::Identifier::Simple(Symbol("Id".into())));
+    /// let x = Dynamic::from_name_with_sort("x", id);
+    /// solver.assert(x._eq(Int::from(3)))?;
+    /// assert_eq!(solver.check_sat()?, SatResult::Sat);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn define_sort(
+        &mut self,
+        name: &str,
+        params: Vec<Symbol>,
+        definition: ast::Sort,
+    ) -> Result<(), Error> {
+        self.declare_tuples([&definition])?;
+        let cmd = ast::Command::DefineSort(Symbol(name.to_string()), params, definition);
+        self.changed();
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(e) => Err(Error::Smt(e.into(), cmd.to_string())),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
     /// Checks for satisfiability of the assertions sent to the solver using
@@ -125,17 +727,134 @@ where
     /// If you are interested in producing a model satisfying the assertions
     /// check out [`Solver::check_sat`].
     pub fn check_sat(&mut self) -> Result<SatResult, Error> {
+        self.exec_check_sat(ast::Command::CheckSat)
+    }
+    /// Checks for satisfiability like [`Solver::check_sat`], additionally
+    /// returning the wall-clock time spent waiting for the solver to respond.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// solver.assert(Int::from_name("x").gt(2))?;
+    /// let (res, elapsed) = solver.check_sat_timed()?;
+    /// println!("{res:?} in {elapsed:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check_sat_timed(&mut self) -> Result<(SatResult, Duration), Error> {
+        let start = Instant::now();
+        let res = self.check_sat()?;
+        Ok((res, start.elapsed()))
+    }
+    /// Checks for satisfiability like [`Solver::check_sat`], but interrupts
+    /// the solver if `cancel` is set while waiting for it, in which case the
+    /// solver answers [`SatResult::Unknown`]. The flag is typically set from
+    /// another thread, such as one handling user input.
+    ///
+    /// The solver is interrupted using [`KillHandle::interrupt`], which
+    /// solvers such as Z3 handle by aborting the check, leaving the solver
+    /// usable for further commands. This requires a backend with a
+    /// [kill handle](backend::Backend::kill_handle), and otherwise returns
+    /// [`Error::Unsupported`]. Solvers which do not handle interrupts, such
+    /// as cvc5, are killed instead, and fail any further commands.
+    ///
+    /// If `cancel` is already set, the check is not sent to the solver at all,
+    /// and [`SatResult::Unknown`] is returned immediately.
+    ///
+    /// > **NOTE:** If `cancel` is set just as the check completes, the
+    /// > interrupt can still arrive after the solver has answered. The solver
+    /// > then has no command to abort, and may be terminated by the interrupt
+    /// > even if it otherwise handles it.
+    ///
+    /// ```
+    /// # use std::sync::atomic::AtomicBool;
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let cancel = AtomicBool::new(false);
+    /// solver.assert(Int::from_name("x").gt(2))?;
+    /// let res = solver.check_sat_cancellable(&cancel)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`KillHandle::interrupt`]: backend::KillHandle::interrupt
+    pub fn check_sat_cancellable(&mut self, cancel: &AtomicBool) -> Result<SatResult, Error> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
         let cmd = ast::Command::CheckSat;
+        let Some(handle) = self.driver.backend().kill_handle() else {
+            return Err(Error::Unsupported(format!("cancelling {cmd}")));
+        };
+        if cancel.load(Ordering::Acquire) {
+            return Ok(SatResult::Unknown);
+        }
+        let done = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            let watcher = s.spawn(|| {
+                while !done.load(Ordering::Acquire) {
+                    if cancel.load(Ordering::Acquire) {
+                        // NOTE: The check may have completed while loading
+                        // `cancel`, in which case there is nothing to
+                        // interrupt
+                        if done.load(Ordering::Acquire) {
+                            return;
+                        }
+                        // NOTE: If interrupting fails the check simply runs
+                        // to completion
+                        let _ = handle.interrupt();
+                        return;
+                    }
+                    std::thread::park_timeout(POLL_INTERVAL);
+                }
+            });
+            let res = self.exec_check_sat(cmd);
+            done.store(true, Ordering::Release);
+            watcher.thread().unpark();
+            res
+        })
+    }
+    /// Checks for satisfiability of the assertions sent to the solver using
+    /// [`Solver::assert`] together with `assumptions`, without asserting the
+    /// assumptions permanently.
+    ///
+    /// Each assumption must be a literal, that is, a boolean constant or its
+    /// negation. Otherwise [`Error::UnexpectedTerm`] is returned.
+    ///
+    /// ```
+    /// # use smtlib::{Bool, SatResult, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let a = Bool::from_name("a");
+    /// solver.assert(!Bool::from(&a))?;
+    /// let res = solver.check_sat_assuming(vec![a.into()])?;
+    /// assert!(matches!(res, SatResult::Unsat));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check_sat_assuming(&mut self, assumptions: Vec<Bool>) -> Result<SatResult, Error> {
+        let mut literals = vec![];
+        for assumption in assumptions {
+            let term = ast::Term::from(assumption);
+            self.declare_all_consts(&term, &[])?;
+            literals.push(prop_literal(term)?);
+        }
+        self.exec_check_sat(ast::Command::CheckSatAssuming(literals))
+    }
+    fn exec_check_sat(&mut self, cmd: ast::Command) -> Result<SatResult, Error> {
         match self.driver.exec(&cmd)? {
             ast::GeneralResponse::SpecificSuccessResponse(
                 ast::SpecificSuccessResponse::CheckSatResponse(res),
-            ) => Ok(match res {
-                ast::CheckSatResponse::Sat => SatResult::Sat,
-                ast::CheckSatResponse::Unsat => SatResult::Unsat,
-                ast::CheckSatResponse::Unknown => SatResult::Unknown,
-            }),
-            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg, format!("{cmd}"))),
-            res => todo!("{res:?}"),
+            ) => {
+                let res = SatResult::from(res);
+                self.changed();
+                self.last_result = Some(res);
+                Ok(res)
+            }
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
         }
     }
     /// Checks for satisfiability of the assertions sent to the solver using
@@ -150,18 +869,1785 @@ where
             SatResult::Unknown => Ok(SatResultWithModel::Unknown),
         }
     }
+    /// Enumerates the models of the assertions which differ in the value of
+    /// at least one of `vars`.
+    ///
+    /// Each call to `next` checks for satisfiability, and if `sat`, produces
+    /// the model and asserts a blocking clause, excluding the values of
+    /// `vars` in that model from future models. The iterator ends once the
+    /// assertions are `unsat`, or after producing an error, such as
+    /// [`Error::UnexpectedSatResult`] if the solver answers `unknown`.
+    ///
+    /// The blocking clauses remain asserted after the iterator is dropped.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// solver.assert(x.in_range(0, 3))?;
+    /// let models = solver.models(&[x.into()]).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(models.len(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn models<'a>(
+        &'a mut self,
+        vars: &'a [Dynamic],
+    ) -> impl Iterator<Item = Result<Model, Error>> + 'a {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let res = self.next_model(vars);
+            done = !matches!(res, Ok(Some(_)));
+            res.transpose()
+        })
+    }
+    fn next_model(&mut self, vars: &[Dynamic]) -> Result<Option<Model>, Error> {
+        let model = match self.check_sat_with_model()? {
+            SatResultWithModel::Sat(model) => model,
+            SatResultWithModel::Unsat => return Ok(None),
+            SatResultWithModel::Unknown => {
+                return Err(Error::UnexpectedSatResult {
+                    expected: SatResult::Sat,
+                    actual: SatResult::Unknown,
+                })
+            }
+        };
+        let mut blocking: Vec<ast::Term> = vec![];
+        for var in vars {
+            let value = self.get_value(var.clone())?;
+            blocking.push(var._neq(value).into());
+        }
+        let clause = match blocking.len() {
+            // NOTE: Without any variables all models are the same, and thus
+            // the empty disjunction `false` blocks all future models
+            0 => Bool::from(false),
+            1 => blocking.pop().unwrap().into(),
+            _ => fun("or", blocking).into(),
+        };
+        self.assert(clause)?;
+        Ok(Some(model))
+    }
     /// Produces the model for satisfying the assertions. If you are looking to
     /// retrieve a model after calling [`Solver::check_sat`], consider using
     /// [`Solver::check_sat_with_model`] instead.
     ///
+    /// If any [labeled](Sort::labeled) terms have been asserted, their values
+    /// are retrieved using `(get-value)` as well, making them available
+    /// through [`Model::eval_label`].
+    ///
+    /// If models are cached using [`SolverBuilder::cache_models`], asking for
+    /// the model again before the state of the solver has changed returns the
+    /// cached model without asking the solver.
+    ///
     /// > **NOTE:** This must only be called after having called
-    /// > [`Solver::check_sat`] and it returning [`SatResult::Sat`].
+    /// > [`Solver::check_sat`] and it returning [`SatResult::Sat`]. If it
+    /// > returned [`SatResult::Unsat`] an error is returned without asking the
+    /// > solver, and otherwise the error reported by the solver is returned.
     pub fn get_model(&mut self) -> Result<Model, Error> {
-        match self.driver.exec(&ast::Command::GetModel)? {
+        if let Some(SatResult::Unsat) = self.last_result {
+            return Err(Error::UnexpectedSatResult {
+                expected: SatResult::Sat,
+                actual: SatResult::Unsat,
+            });
+        }
+        if let Some((generation, model)) = &self.cached_model {
+            if *generation == self.generation {
+                return Ok(model.clone());
+            }
+        }
+        let cmd = ast::Command::GetModel;
+        let mut model = match self.driver.exec(&cmd)? {
             ast::GeneralResponse::SpecificSuccessResponse(
                 ast::SpecificSuccessResponse::GetModelResponse(model),
-            ) => Ok(Model::new(model)),
-            res => todo!("{res:?}"),
+            ) => Model::new(model)?,
+            ast::GeneralResponse::Unsupported => return Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => {
+                return Err(Error::Smt(msg.into(), format!("{cmd}")))
+            }
+            res => return Err(Error::unexpected_response(&cmd, res)),
+        };
+        if !self.labels.is_empty() {
+            model.labels = self.label_values()?;
+        }
+        if self.cache_models {
+            self.cached_model = Some((self.generation, model.clone()));
+        }
+        Ok(model)
+    }
+    fn label_values(&mut self) -> Result<HashMap<String, ast::Term>, Error> {
+        let cmd = ast::Command::GetValue(
+            self.labels
+                .iter()
+                .map(|name| {
+                    ast::Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(
+                        name.clone(),
+                    )))
+                })
+                .collect(),
+        );
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetValueResponse(res),
+            ) => Ok(self
+                .labels
+                .iter()
+                .zip(res.0)
+                .map(|(name, ast::ValuationPair(_, value))| (name.0.clone(), value))
+                .collect()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Produces the reason for the last `check-sat` returning
+    /// [`SatResult::Unknown`], as reported by `(get-info :reason-unknown)`.
+    /// Common reasons are `timeout`, `memout`, and `incomplete`, but solvers
+    /// are free to give any explanation.
+    pub fn reason_unknown(&mut self) -> Result<String, Error> {
+        let cmd = ast::Command::GetInfo(ast::InfoFlag::ReasonUnknown);
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetInfoResponse(res),
+            ) => res
+                .0
+                .into_iter()
+                .find_map(|info| match info {
+                    ast::InfoResponse::ReasonUnknown(reason) => Some(match reason {
+                        ast::ReasonUnknown::SExpr(ast::SExpr::SpecConstant(
+                            ast::SpecConstant::String(s),
+                        )) => s[1..s.len() - 1].replace("\"\"", "\""),
+                        reason => reason.to_string(),
+                    }),
+                    _ => None,
+                })
+                .ok_or_else(|| Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Sends `(echo "msg")`, to which the solver responds with `msg`
+    /// unchanged. This is useful for delimiting sections of the
+    /// [transcript](Solver::transcript).
+    pub fn echo(&mut self, msg: &str) -> Result<String, Error> {
+        let cmd = ast::Command::Echo(format!("\"{}\"", msg.replace('"', "\"\"")));
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::EchoResponse(ast::EchoResponse(s)),
+            ) => Ok(s[1..s.len() - 1].replace("\"\"", "\"")),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Sends `(set-info :keyword value)`, attaching information such as
+    /// `:status` or `:source` to the problem. The `value` is parsed as an
+    /// SMT-LIB attribute value, such as a symbol, string literal, or numeral.
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// solver.set_info("status", "unsat")?;
+    /// solver.set_info("source", r#""generated by my tool""#)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_info(&mut self, keyword: &str, value: &str) -> Result<(), Error> {
+        let value = ast::AttributeValue::parse(value).map_err(smtlib_lowlevel::Error::from)?;
+        let cmd = ast::Command::SetInfo(ast::Attribute::WithValue(
+            Keyword(format!(":{}", keyword.trim_start_matches(':'))),
+            value,
+        ));
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::Success => Ok(()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Retrieves the value of `x` in the model produced by the last
+    /// `check-sat`, as reported by `(get-value)`. Unlike [`Model::eval`],
+    /// `x` is not limited to constants, but can be any term over declared
+    /// constants.
+    ///
+    /// > **NOTE:** This must only be called after having called
+    /// > [`Solver::check_sat`] and it returning [`SatResult::Sat`].
+    pub fn get_value<T: Sort>(&mut self, x: T) -> Result<T::Inner, Error>
+    where
+        T::Inner: From<ast::Term>,
+    {
+        Ok(self.get_value_of(x.into().strip_sort())?.into())
+    }
+    /// Retrieves the value of the term labeled by `label` using
+    /// [`Sort::labeled`], in the model produced by the last `check-sat`.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// let x = Int::from_name("x");
+    /// let (label, doubled) = (x * 2).labeled();
+    /// solver.assert(doubled._eq(8))?;
+    /// solver.check_sat()?;
+    /// assert_eq!(i64::try_from(solver.value_of_label(&label)?)?, 8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// > **NOTE:** This must only be called after having called
+    /// > [`Solver::check_sat`] and it returning [`SatResult::Sat`], and the
+    /// > labeled term must be part of an assertion.
+    pub fn value_of_label<T: Sort>(&mut self, label: &Label<T>) -> Result<T::Inner, Error>
+    where
+        T::Inner: From<ast::Term>,
+    {
+        let name = ast::Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(Symbol(
+            label.name(),
+        ))));
+        Ok(self.get_value_of(name)?.into())
+    }
+    fn get_value_of(&mut self, term: ast::Term) -> Result<ast::Term, Error> {
+        let cmd = ast::Command::GetValue(vec![term]);
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetValueResponse(res),
+            ) => {
+                // The response contains a value for each of the terms
+                let ast::ValuationPair(_, value) = res.0.into_iter().next().unwrap();
+                Ok(value)
+            }
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Produces the proof of unsatisfiability of the assertions, as the raw
+    /// text returned by `(get-proof)`. The format of proofs is specific to
+    /// each solver.
+    ///
+    /// > **NOTE:** This must only be called after having called
+    /// > [`Solver::check_sat`] and it returning [`SatResult::Unsat`], and
+    /// > returns an error if it returned anything else.
+    ///
+    /// This requires proofs to be enabled using
+    /// [`SolverBuilder::produce_proofs`], and otherwise fails with
+    /// [`Error::OptionDisabled`].
+    pub fn get_proof(&mut self) -> Result<String, Error> {
+        if !self.produce_proofs {
+            return Err(Error::OptionDisabled {
+                option: ":produce-proofs",
+            });
+        }
+        if let Some(actual @ (SatResult::Sat | SatResult::Unknown)) = self.last_result {
+            return Err(Error::UnexpectedSatResult {
+                expected: SatResult::Unsat,
+                actual,
+            });
+        }
+        let cmd = ast::Command::GetProof;
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetProofResponse(proof),
+            ) => Ok(proof.to_string()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Produces the subset of the assumptions given to the last call of
+    /// [`Solver::check_sat_assuming`] which were used to conclude `unsat`, as
+    /// reported by `(get-unsat-assumptions)`.
+    ///
+    /// > **NOTE:** This must only be called after having called
+    /// > [`Solver::check_sat_assuming`] and it returning
+    /// > [`SatResult::Unsat`], and returns an error if it returned anything
+    /// > else.
+    ///
+    /// This requires unsat assumptions to be enabled using
+    /// [`SolverBuilder::produce_unsat_assumptions`], and otherwise fails with
+    /// [`Error::OptionDisabled`].
+    pub fn get_unsat_assumptions(&mut self) -> Result<Vec<Bool>, Error> {
+        if !self.produce_unsat_assumptions {
+            return Err(Error::OptionDisabled {
+                option: ":produce-unsat-assumptions",
+            });
+        }
+        if let Some(actual @ (SatResult::Sat | SatResult::Unknown)) = self.last_result {
+            return Err(Error::UnexpectedSatResult {
+                expected: SatResult::Unsat,
+                actual,
+            });
         }
+        let cmd = ast::Command::GetUnsatAssumptions;
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetUnsatAssumptionsResponse(res),
+            ) => Ok(res
+                .0
+                .into_iter()
+                .map(|sym| ast::Term::Identifier(qual_ident(sym.0, Some(Bool::sort()))).into())
+                .collect()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Computes an interpolant between the current assertions `A` and
+    /// `conjecture`, using `(get-interpolant name conjecture)`. The
+    /// interpolant is a formula `I` only mentioning symbols shared between
+    /// `A` and `conjecture`, such that `A` implies `I` and `I` implies
+    /// `conjecture`. The solver names the interpolant `name`, which must
+    /// not already be declared.
+    ///
+    /// This requires `:produce-interpolants` to be enabled before any
+    /// assertions are made, for example using
+    /// [`SolverBuilder::produce_interpolants`], and fails if `A` does not
+    /// imply `conjecture`.
+    ///
+    /// > **NOTE:** Interpolation is not part of the SMT-LIB standard. This is
+    /// > the form supported by cvc5, while recent versions of z3 do not
+    /// > support interpolation at all.
+    pub fn get_interpolant(&mut self, name: &str, conjecture: Bool) -> Result<Bool, Error> {
+        let term = ast::Term::from(conjecture);
+        self.declare_all_consts(&term, &[])?;
+        let cmd = ast::Command::GetInterpolant(Symbol(name.to_string()), term);
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetInterpolantResponse(res),
+            ) => Ok(res.0 .3.into()),
+            ast::GeneralResponse::Unsupported => Err(Error::Unsupported(cmd.to_string())),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// Retrieves the assertions currently on the assertion stack of the
+    /// solver, as reported by `(get-assertions)`. This is mostly useful for
+    /// debugging.
+    pub fn get_assertions(&mut self) -> Result<Vec<ast::Term>, Error> {
+        let cmd = ast::Command::GetAssertions;
+        match self.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetAssertionsResponse(res),
+            ) => Ok(res.0),
+            ast::GeneralResponse::Error(msg) => Err(Error::Smt(msg.into(), format!("{cmd}"))),
+            res => Err(Error::unexpected_response(&cmd, res)),
+        }
+    }
+    /// The constants declared so far, along with their sorts, whether
+    /// declared explicitly using [`Solver::declare_const`] or on behalf of
+    /// [`Solver::assert`]. The order is unspecified.
+    ///
+    /// ```
+    /// # use smtlib::{Int, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut solver = smtlib::Solver::new(smtlib::backend::Z3Binary::new("z3")?, false)?;
+    /// solver.assert(Int::from_name("x").gt(2))?;
+    /// for (name, sort) in solver.declarations() {
+    ///     println!("{name}: {sort}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn declarations(&self) -> impl Iterator<Item = (&Identifier, &ast::Sort)> {
+        self.decls.iter()
+    }
+    /// Sets whether commands sent to the solver are recorded in the
+    /// [transcript](Solver::transcript). This is disabled by default, as the
+    /// transcript grows with every command, and can also be enabled using
+    /// [`SolverBuilder::record_transcript`].
+    pub fn record_transcript(&mut self, enable: bool) {
+        if !self.driver.records_transcript() {
+            // NOTE: Every command changing the state of the solver bumps the
+            // generation, so none have been missed if it is still zero
+            self.transcript_complete = enable && self.generation == 0;
+        } else if !enable {
+            self.transcript_complete = false;
+        }
+        self.driver.record_transcript(enable);
+    }
+    /// Every command sent to the solver while recording was enabled using
+    /// [`Solver::record_transcript`], including declarations made on behalf
+    /// of [`Solver::assert`], as an SMT-LIB2 script. This is useful for
+    /// reproducing issues outside of Rust.
+    pub fn transcript(&self) -> &str {
+        self.driver.transcript()
+    }
+    /// Discards the commands recorded in the [transcript](Solver::transcript)
+    /// so far.
+    pub fn clear_transcript(&mut self) {
+        self.transcript_complete = false;
+        self.driver.clear_transcript();
+    }
+    /// Writes the [transcript](Solver::transcript) to the file at `path`, such
+    /// that it can be run directly by a solver, for example using
+    /// `z3 file.smt2`.
+    pub fn save_transcript(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, self.transcript()).map_err(smtlib_lowlevel::Error::from)?;
+        Ok(())
+    }
+    /// Constructs an independent solver using `backend`, with the same
+    /// assertions, declarations, and options as this solver. This is done by
+    /// replaying the [transcript](Solver::transcript) into `backend`, which
+    /// makes it possible to explore different continuations of a shared
+    /// prefix of assertions.
+    ///
+    /// This requires the transcript to have been recorded since the solver
+    /// was constructed or last [reset](Solver::reset), for example using
+    /// [`SolverBuilder::record_transcript`], and not cleared since. Otherwise
+    /// the transcript is incomplete, and this fails with
+    /// [`Error::TranscriptNotRecorded`].
+    ///
+    /// Commands which only retrieve information, such as `check-sat` and
+    /// `get-model`, are not replayed, and thus the forked solver must check
+    /// for satisfiability before retrieving a model.
+    ///
+    /// ```
+    /// # use smtlib::{backend::Z3Binary, Int, SatResult, SolverBuilder, Sort};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut solver = SolverBuilder::new()
+    ///     .record_transcript(true)
+    ///     .build(Z3Binary::new("z3")?)?;
+    /// let x = Int::from_name("x");
+    /// solver.assert(x.gt(5))?;
+    /// let mut fork = solver.fork(Z3Binary::new("z3")?)?;
+    /// fork.assert(x.lt(3))?;
+    /// assert_eq!(fork.check_sat()?, SatResult::Unsat);
+    /// assert_eq!(solver.check_sat()?, SatResult::Sat);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fork(&self, backend: B) -> Result<Solver<B>, Error> {
+        if !self.transcript_complete || !self.driver.records_transcript() {
+            return Err(Error::TranscriptNotRecorded);
+        }
+        let script = ast::Script::parse(self.transcript()).map_err(smtlib_lowlevel::Error::from)?;
+        let cmds: Vec<_> = script
+            .0
+            .into_iter()
+            .filter(|cmd| !cmd.has_response())
+            .collect();
+        let mut driver = Driver::new(backend, self.driver.verbose())?;
+        // NOTE: The default options are sent by `Solver::new` before the
+        // transcript can be recorded, and thus are not part of it
+        Self::set_default_options(&mut driver)?;
+        driver.record_transcript(true);
+        // NOTE: Commands which failed originally fail the same way when
+        // replayed, leaving the solver in the same state, so the responses
+        // are not inspected
+        driver.exec_batch(&cmds)?;
+        Ok(Solver {
+            driver,
+            decls: self.decls.clone(),
+            tuples: self.tuples.clone(),
+            labels: self.labels.clone(),
+            last_result: None,
+            logic: self.logic.clone(),
+            validate_logic: self.validate_logic,
+            generation: 0,
+            cached_model: None,
+            cache_models: self.cache_models,
+            transcript_complete: true,
+            produce_proofs: self.produce_proofs,
+            produce_unsat_assumptions: self.produce_unsat_assumptions,
+            scopes: self.scopes.clone(),
+        })
+    }
+}
+
+/// A builder for configuring the options of a [`Solver`] before it starts
+/// receiving commands. Each option corresponds to a `set-option` command,
+/// which are sent in the order they were configured when calling
+/// [`SolverBuilder::build`].
+///
+/// ```
+/// # use smtlib::{SolverBuilder, backend::Z3Binary};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut solver = SolverBuilder::new()
+///     .produce_unsat_cores(true)
+///     .random_seed(42)
+///     .build(Z3Binary::new("z3")?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct SolverBuilder {
+    verbose: bool,
+    validate_logic: bool,
+    cache_models: bool,
+    record_transcript: bool,
+    options: Vec<ast::Option>,
+}
+
+impl SolverBuilder {
+    /// Construct a builder with no options configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets whether the commands sent to and the responses received from the
+    /// solver should be printed.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+    /// Sets whether terms passed to [`Solver::assert`] should be checked
+    /// against the logic set by [`Solver::set_logic`] before being sent to
+    /// the solver, rejecting for example quantifiers in a quantifier-free
+    /// logic with [`Error::UnsupportedByLogic`]. This is disabled by default.
+    pub fn validate_logic(mut self, enable: bool) -> Self {
+        self.validate_logic = enable;
+        self
+    }
+    /// Sets whether [`Solver::get_model`] should reuse the model it produced
+    /// last, if the state of the solver has not changed since. Any command
+    /// changing the state, such as asserting, declaring, pushing, popping, or
+    /// checking satisfiability, invalidates the cached model. This is
+    /// disabled by default.
+    pub fn cache_models(mut self, enable: bool) -> Self {
+        self.cache_models = enable;
+        self
+    }
+    /// Sets whether commands sent to the solver are recorded in its
+    /// [transcript](Solver::transcript), which is required by
+    /// [`Solver::fork`]. This is disabled by default.
+    pub fn record_transcript(mut self, enable: bool) -> Self {
+        self.record_transcript = enable;
+        self
+    }
+    /// Sets `:produce-models`, required by some solvers for
+    /// [`Solver::get_model`].
+    pub fn produce_models(mut self, enable: bool) -> Self {
+        self.options.push(ast::Option::ProduceModels(enable));
+        self
+    }
+    /// Sets `:produce-unsat-cores`, required for retrieving unsat cores.
+    pub fn produce_unsat_cores(mut self, enable: bool) -> Self {
+        self.options.push(ast::Option::ProduceUnsatCores(enable));
+        self
+    }
+    /// Sets `:produce-proofs`, required for [`Solver::get_proof`].
+    pub fn produce_proofs(mut self, enable: bool) -> Self {
+        self.options.push(ast::Option::ProduceProofs(enable));
+        self
+    }
+    /// Sets `:produce-unsat-assumptions`, required for
+    /// [`Solver::get_unsat_assumptions`].
+    pub fn produce_unsat_assumptions(mut self, enable: bool) -> Self {
+        self.options
+            .push(ast::Option::ProduceUnsatAssumptions(enable));
+        self
+    }
+    /// Sets `:produce-interpolants`, required for
+    /// [`Solver::get_interpolant`].
+    ///
+    /// > **NOTE:** This option is not part of the SMT-LIB standard, but is
+    /// > supported by for example cvc5.
+    pub fn produce_interpolants(mut self, enable: bool) -> Self {
+        self.options.push(ast::Option::ProduceInterpolants(enable));
+        self
+    }
+    /// Sets `:random-seed`, which some solvers use to make their heuristics
+    /// reproducible.
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.options
+            .push(ast::Option::RandomSeed(Numeral(seed.to_string())));
+        self
+    }
+    /// Sets `:timeout` in milliseconds, after which `check-sat` gives up and
+    /// reports `unknown`.
+    ///
+    /// > **NOTE:** This option is not part of the SMT-LIB standard, but is
+    /// > supported by for example z3.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.push(ast::Option::Timeout(Numeral(
+            timeout.as_millis().to_string(),
+        )));
+        self
+    }
+    /// Construct a [`Solver`] using `backend`, and send the configured options
+    /// to it.
+    pub fn build<B: backend::Backend>(self, backend: B) -> Result<Solver<B>, Error> {
+        let mut solver = Solver::new(backend, self.verbose)?;
+        solver.validate_logic = self.validate_logic;
+        solver.cache_models = self.cache_models;
+        solver.record_transcript(self.record_transcript);
+        self.configure(&mut solver)?;
+        Ok(solver)
+    }
+    /// Sends the configured options to an already constructed `solver`.
+    pub(crate) fn configure<B: backend::Backend>(
+        &self,
+        solver: &mut Solver<B>,
+    ) -> Result<(), Error> {
+        for option in &self.options {
+            solver.set_option(option.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts `term` to a literal for `check-sat-assuming`, that is, either a
+/// boolean constant or the negation of one.
+fn prop_literal(term: ast::Term) -> Result<ast::PropLiteral, Error> {
+    let symbol = |t: &ast::Term| match t {
+        ast::Term::Identifier(
+            QualIdentifier::Identifier(Identifier::Simple(sym))
+            | QualIdentifier::Sorted(Identifier::Simple(sym), _),
+        ) => Some(sym.clone()),
+        _ => None,
+    };
+    let literal = match &term {
+        ast::Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args)
+            if op.0 == "not" && args.len() == 1 =>
+        {
+            symbol(&args[0]).map(ast::PropLiteral::Not)
+        }
+        t => symbol(t).map(ast::PropLiteral::Symbol),
+    };
+    literal.ok_or_else(|| Error::UnexpectedTerm {
+        expected: "a boolean constant or its negation",
+        term: term.to_string(),
+    })
+}
+
+/// Produces the commands declaring the constants in `term` which are not
+/// already in `decls`, except for those bound by `bound`, and records them in
+/// `decls`. The tuple datatypes used by the constants and in `bound` are
+/// likewise declared unless they are in `tuples`.
+///
+/// If `term` uses a constant with a different sort than it is declared with,
+/// [`Error::SortMismatch`] is returned, and `decls` and `tuples` are left
+/// unchanged.
+///
+/// This is shared between [`Solver`] and
+/// [`AsyncSolver`](crate::AsyncSolver), which only differ in how the commands
+/// are executed.
+pub(crate) fn declarations(
+    decls: &mut HashMap<Identifier, ast::Sort>,
+    tuples: &mut HashSet<String>,
+    term: &ast::Term,
+    bound: &[SortedVar],
+) -> Result<Vec<ast::Command>, Error> {
+    // NOTE: Identifiers heading an application, such as `const` in
+    // `((as const (Array Int Int)) 0)`, are functions rather than constants
+    let mut new: Vec<(&Symbol, &ast::Sort)> = vec![];
+    for q in term.all_unapplied_consts() {
+        match q {
+            QualIdentifier::Identifier(_) => {}
+            QualIdentifier::Sorted(Identifier::Simple(sym), _)
+                if bound.iter().any(|var| &var.0 == sym) => {}
+            // The empty set is qualified by its sort, but is not a constant
+            _ if sets::is_empty_set(q) => {}
+            // Indexed identifiers, such as `(_ bv1 8)`, are theory symbols and
+            // cannot be declared
+            QualIdentifier::Sorted(Identifier::Indexed(_, _), _) => {}
+            QualIdentifier::Sorted(i @ Identifier::Simple(sym), s) => {
+                let stored = decls
+                    .get(i)
+                    .or_else(|| new.iter().find(|(n, _)| n == &sym).map(|(_, s)| *s));
+                match stored {
+                    Some(stored) if stored == s => {}
+                    Some(stored) => {
+                        return Err(Error::SortMismatch {
+                            expected: stored.to_string(),
+                            actual: s.to_string(),
+                        })
+                    }
+                    None => new.push((sym, s)),
+                }
+            }
+        }
+    }
+    let consts = term.all_consts();
+    let sorts = consts.iter().filter_map(|q| match q {
+        QualIdentifier::Identifier(_) => None,
+        QualIdentifier::Sorted(_, s) => Some(s),
+    });
+    let mut cmds = tuple_declarations(tuples, sorts.chain(bound.iter().map(|var| &var.1)));
+    for (sym, s) in new {
+        decls.insert(Identifier::Simple(sym.clone()), s.clone());
+        cmds.push(ast::Command::DeclareConst(sym.clone(), s.clone()));
+    }
+    Ok(cmds)
+}
+
+/// Records the names of the [`Label`]s in `term` in `labels`, unless they are
+/// already there.
+fn labels(term: &ast::Term, labels: &mut Vec<Symbol>) {
+    match term {
+        ast::Term::SpecConstant(_) | ast::Term::Identifier(_) => {}
+        ast::Term::Application(_, args) => {
+            for arg in args {
+                self::labels(arg, labels);
+            }
+        }
+        ast::Term::Let(bindings, body) => {
+            for binding in bindings {
+                self::labels(&binding.1, labels);
+            }
+            self::labels(body, labels);
+        }
+        ast::Term::Forall(_, body) | ast::Term::Exists(_, body) => self::labels(body, labels),
+        ast::Term::Match(t, cases) => {
+            self::labels(t, labels);
+            for case in cases {
+                self::labels(&case.1, labels);
+            }
+        }
+        ast::Term::Annotation(t, attrs) => {
+            for attr in attrs {
+                if let ast::Attribute::WithValue(Keyword(k), ast::AttributeValue::Symbol(name)) =
+                    attr
+                {
+                    if k == ":named" && name.0.starts_with(LABEL_PREFIX) && !labels.contains(name) {
+                        labels.push(name.clone());
+                    }
+                }
+            }
+            self::labels(t, labels);
+        }
+    }
+}
+
+/// Produces the commands declaring the tuple datatypes used in `sorts`, such
+/// as `Tuple2` in `(Array Int (Tuple2 Int Bool))`, which are not already in
+/// `declared`, and records them in `declared`.
+fn tuple_declarations<'a>(
+    declared: &mut HashSet<String>,
+    sorts: impl IntoIterator<Item = &'a ast::Sort>,
+) -> Vec<ast::Command> {
+    fn go(declared: &mut HashSet<String>, sort: &ast::Sort, cmds: &mut Vec<ast::Command>) {
+        if let ast::Sort::Parametric(Identifier::Simple(name), args) = sort {
+            for arg in args {
+                go(declared, arg, cmds);
+            }
+            if let Some(cmd) = tuples::declaration(&name.0) {
+                if declared.insert(name.0.clone()) {
+                    cmds.push(cmd);
+                }
+            }
+        }
+    }
+    let mut cmds = vec![];
+    for sort in sorts {
+        go(declared, sort, &mut cmds);
+    }
+    cmds
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    use smtlib_lowlevel::{
+        ast,
+        backend::{Cvc5Binary, MockBackend, RecordingBackend, Z3Binary},
+        lexicon::{Keyword, Numeral, Symbol},
+    };
+
+    use crate::{
+        distinct,
+        terms::{exists, Const, Dynamic, QuantifierVars, Sort},
+        theories::{arrays::Array, sets::Set},
+        BitVec, Bool, Error, Int, Logic, Real, SatResult, SatSolver, Solver, SolverBuilder,
+    };
+
+    #[test]
+    fn mock_backend() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = MockBackend::scripted(["sat", "((define-fun x () Int 3))"]);
+        let mut solver = Solver::new(backend, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 3);
+        assert!(solver.check_sat().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn unexpected_check_sat_response() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+
+        let mut solver = Solver::new(MockBackend::scripted(["unsupported"]), false)?;
+        solver.assert(x.gt(2))?;
+        assert!(matches!(solver.check_sat(), Err(Error::Unsupported(_))));
+
+        let mut solver = Solver::new(MockBackend::scripted(["success"]), false)?;
+        solver.assert(x.gt(2))?;
+        assert!(matches!(
+            solver.check_sat(),
+            Err(Error::UnexpectedResponse { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn echo_and_set_info() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        solver.set_info("status", "sat")?;
+        let msg = r#"section "two" (of 3)"#;
+        assert_eq!(solver.echo(msg)?, msg);
+        assert!(solver
+            .transcript()
+            .contains(r#"(echo "section ""two"" (of 3)")"#));
+        assert!(solver.transcript().contains("(set-info :status sat)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_model_before_check_sat() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        assert!(matches!(solver.get_model(), Err(Error::Smt(..))));
+
+        solver.assert(x.lt(2))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+        assert!(matches!(
+            solver.get_model(),
+            Err(Error::UnexpectedSatResult { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn declare_indexed() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        // `(as (_ bv5 8) (_ BitVec 8))` is a theory symbol, and must not be
+        // declared, while `a` of the indexed sort `(_ BitVec 8)` must be
+        let five = ast::Term::Identifier(ast::QualIdentifier::Sorted(
+            ast::Identifier::Indexed(
+                Symbol("bv5".to_string()),
+                vec![ast::Index::Numeral(Numeral("8".to_string()))],
+            ),
+            BitVec::<8>::sort(),
+        ));
+        let a = BitVec::<8>::from_name("a");
+        solver.assert(a._eq(BitVec::<8>::from(five)))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        let transcript = solver.transcript();
+        assert!(transcript.contains("(declare-const a (_ BitVec 8))"));
+        assert_eq!(transcript.matches("declare-const").count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_logic() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+        let quantified = exists(x.clone(), x.gt(0));
+
+        let mut solver = SolverBuilder::new()
+            .validate_logic(true)
+            .record_transcript(true)
+            .build(Z3Binary::new("z3")?)?;
+        solver.set_logic(Logic::QF_LIA)?;
+        solver.assert(x.gt(2))?;
+        let err = solver.assert(quantified.clone()).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedByLogic { .. }), "{err}");
+        let err = solver.assert(Real::from_name("r").gt(0.5)).unwrap_err();
+        assert!(err.to_string().contains("uses the sort Real"), "{err}");
+        assert!(!solver.transcript().contains("exists"));
+
+        solver.reset()?;
+        solver.assert(quantified)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_script() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let responses = solver.load_script("(declare-const x Int)\n(assert (> x 0))")?;
+        assert!(responses
+            .iter()
+            .all(|res| *res == ast::GeneralResponse::Success));
+
+        // `x` is already declared by the script, so this must not declare it again
+        solver.assert(Int::from_name("x").lt(0))?;
+        assert_eq!(solver.transcript().matches("declare-const").count(), 1);
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_script_assertion_stack() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(MockBackend::scripted(["sat"]), false)?;
+        solver.record_transcript(true);
+
+        let script = "(push 1)\n(declare-const y Int)\n(pop 1)\n(declare-const z Int)";
+        assert_eq!(solver.load_script(script)?.len(), 4);
+        solver.assert(Int::from_name("y").gt(Int::from_name("z")))?;
+        assert_eq!(
+            solver.transcript().matches("(declare-const y Int)").count(),
+            2
+        );
+        assert_eq!(
+            solver.transcript().matches("(declare-const z Int)").count(),
+            1
+        );
+
+        solver.load_script("(reset)")?;
+        solver.assert(Int::from_name("z").gt(0))?;
+        assert!(solver.transcript().contains("(declare-const z Int)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_script_rejects_print_success() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(MockBackend::scripted(["sat"]), false)?;
+        solver.record_transcript(true);
+
+        for script in [
+            "(declare-const x Int)\n(set-option :print-success false)",
+            "(reset-assertions)",
+        ] {
+            let err = solver.load_script(script).unwrap_err();
+            assert!(matches!(err, Error::Unsupported(_)), "{err}");
+        }
+        assert_eq!(solver.transcript(), "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_script_declarations() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .validate_logic(true)
+            .record_transcript(true)
+            .build(MockBackend::scripted(["sat"]))?;
+
+        let script = "(set-logic QF_LIA)\n(declare-fun x () Int)\n(declare-fun f (Int) Int)";
+        solver.load_script(script)?;
+        solver.assert(Int::from_name("x").gt(0))?;
+        assert_eq!(solver.transcript().matches("(declare-").count(), 2);
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        let y = Int::from_name("y");
+        let quantified = exists(y.clone(), y.gt(0));
+        assert!(matches!(
+            solver.assert(quantified),
+            Err(Error::UnsupportedByLogic { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn quiet_unless_verbose() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: The test harness captures what tests print, so the test is
+        // rerun in a child process, whose stdout is inspected instead
+        const CHILD: &str = "SMTLIB_QUIET_UNLESS_VERBOSE";
+        if let Some(verbose) = std::env::var_os(CHILD) {
+            print!("<start>");
+            let mut solver = Solver::new(MockBackend::scripted(["sat"]), verbose == "1")?;
+            solver.set_logic(Logic::QF_LIA)?;
+            solver.assert(Int::from_name("x").gt(2))?;
+            solver.check_sat()?;
+            print!("<end>");
+            return Ok(());
+        }
+
+        let run = |verbose: &str| -> Result<String, Box<dyn std::error::Error>> {
+            let output = std::process::Command::new(std::env::current_exe()?)
+                .args(["solver::tests::quiet_unless_verbose", "--exact"])
+                .args(["--nocapture", "--test-threads=1"])
+                .env(CHILD, verbose)
+                .output()?;
+            assert!(output.status.success());
+            Ok(String::from_utf8(output.stdout)?)
+        };
+        let quiet = run("0")?;
+        assert!(quiet.contains("<start><end>"), "{quiet}");
+        let verbose = run("1")?;
+        assert!(verbose.contains("> (check-sat)"), "{verbose}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_assertions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x.gt(&y))?;
+        solver.assert(y._eq(4))?;
+
+        let assertions: Vec<String> = solver
+            .get_assertions()?
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(assertions, ["(> x y)", "(= y 4)"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn define_fun() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let double =
+            solver.define_fun::<Int>("double", x.clone().into_vars(), Int::sort(), x * 2)?;
+
+        let y = Int::from_name("y");
+        solver.assert(double.call([Int::from(3).into()])._eq(6))?;
+        solver.assert(double.call([(&y).into()])._eq(10))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Sat));
+
+        let model = solver.get_model()?;
+        assert_eq!(model.eval(y).unwrap().to_string(), "5");
+
+        Ok(())
+    }
+    #[test]
+    fn declare_fun() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let f = solver.declare_fun::<Int>("f", vec![Int::sort()], Int::sort())?;
+        solver.assert(
+            f.call([Int::from(0).into()])
+                ._eq(f.call([Int::from(1).into()])),
+        )?;
+        solver.assert(Int::from(0)._neq(1))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Sat));
+        assert!(solver.transcript().contains("(declare-fun f (Int) Int)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_all() -> Result<(), Box<dyn std::error::Error>> {
+        let xs: Vec<_> = (0..10).map(|i| Int::from_name(format!("x{i}"))).collect();
+        let constraints = || {
+            (0..100).map(|i| {
+                let x = &xs[i % 10];
+                let y = &xs[(i + 1) % 10];
+                x.ge(0) & (x + y).lt(i as i64 + 10)
+            })
+        };
+
+        let mut looped = Solver::new(Z3Binary::new("z3")?, false)?;
+        for b in constraints() {
+            looped.assert(b)?;
+        }
+
+        let mut batched = Solver::new(Z3Binary::new("z3")?, false)?;
+        batched.record_transcript(true);
+        batched.assert_all(constraints())?;
+        assert_eq!(batched.check_sat()?, looped.check_sat()?);
+
+        let transcript = batched.transcript();
+        assert_eq!(transcript.matches("(declare-const ").count(), 10);
+        assert_eq!(transcript.matches("(assert ").count(), 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transcript() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        solver.assert(x.lt(4))?;
+        solver.check_sat()?;
+
+        let transcript = solver.transcript();
+        assert!(transcript.contains("(declare-const x Int)"));
+        assert_eq!(transcript.matches("(assert ").count(), 2);
+        assert!(transcript.ends_with("(check-sat)\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn transcript_is_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+        let mut solver = Solver::new(MockBackend::scripted(["sat"]), false)?;
+        solver.assert(x.gt(2))?;
+        assert_eq!(solver.transcript(), "");
+        assert!(matches!(
+            solver.fork(MockBackend::scripted(["sat"])),
+            Err(Error::TranscriptNotRecorded)
+        ));
+
+        solver.record_transcript(true);
+        solver.check_sat()?;
+        assert_eq!(solver.transcript(), "(check-sat)\n");
+        solver.clear_transcript();
+        assert_eq!(solver.transcript(), "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_proof() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .produce_proofs(true)
+            .build(Z3Binary::new("z3")?)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Sat));
+        assert!(solver.get_proof().is_err());
+
+        solver.assert(x.lt(2))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+        assert!(!solver.get_proof()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn proofs_are_opt_in() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(MockBackend::scripted(["unsat", "unsat"]), false)?;
+        solver.record_transcript(true);
+        solver.assert(Bool::from(false))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+        let err = solver.get_proof().unwrap_err();
+        assert!(matches!(err, Error::OptionDisabled { .. }), "{err}");
+        let res = solver.check_sat_assuming(vec![Bool::from(true)])?;
+        assert_eq!(res, SatResult::Unsat);
+        let err = solver.get_unsat_assumptions().unwrap_err();
+        assert!(matches!(err, Error::OptionDisabled { .. }), "{err}");
+        assert!(!solver.transcript().contains("(get-"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn value_of_label() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        let (label, sum) = (&x + &y).labeled();
+        solver.assert(sum.gt(4))?;
+        solver.assert(x._eq(2))?;
+        solver.assert(y.lt(4))?;
+        assert!(solver.transcript().contains(":named named-label-"));
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        assert_eq!(i64::try_from(solver.value_of_label(&label)?)?, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn eval_label() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let (label, sum) = (&x + 3).labeled();
+        let (unused, _) = (&x * 2).labeled();
+        solver.assert(sum._eq(10))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval_label(&label).unwrap())?, 10);
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 7);
+        assert!(model.eval_label(&unused).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_annotated_exists() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        let (_, b) = exists(x.clone(), x.gt(&y)).labeled();
+        solver.assert(b)?;
+
+        let transcript = solver.transcript();
+        assert!(transcript.contains("(declare-const y Int)"));
+        assert!(!transcript.contains("(declare-const x Int)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_sat_assuming() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let a = Bool::from_name("a");
+        let b = Bool::from_name("b");
+        solver.assert(a.implies(Bool::from(&b)))?;
+
+        let res = solver.check_sat_assuming(vec![a.clone().into(), b.clone().into()])?;
+        assert!(matches!(res, SatResult::Sat));
+        let res = solver.check_sat_assuming(vec![a.into(), !Bool::from(b)])?;
+        assert!(matches!(res, SatResult::Unsat));
+
+        // The assumptions are not asserted permanently
+        assert!(matches!(solver.check_sat()?, SatResult::Sat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_unsat_assumptions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .produce_unsat_assumptions(true)
+            .build(Z3Binary::new("z3")?)?;
+
+        let a = Bool::from_name("a");
+        let b = Bool::from_name("b");
+        let c = Bool::from_name("c");
+        solver.assert(!(Bool::from(&a) & &b))?;
+
+        let res = solver.check_sat_assuming(vec![a.clone().into(), b.clone().into(), c.into()])?;
+        assert_eq!(res, SatResult::Unsat);
+        let mut core: Vec<_> = solver
+            .get_unsat_assumptions()?
+            .into_iter()
+            .map(|b| b.to_string())
+            .collect();
+        core.sort();
+        assert_eq!(core, [a.to_string(), b.to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn declare_const() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = solver.declare_const::<Int>("x")?;
+        assert!(solver.transcript().contains("(declare-const x Int)"));
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        i64::try_from(solver.get_value(x)?)?;
+
+        // Redeclaring with the same sort is a no-op, but not with another
+        solver.declare_const::<Int>("x")?;
+        assert_eq!(solver.transcript().matches("(declare-const x").count(), 1);
+        assert!(solver.declare_const::<Bool>("x").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejected_declarations_are_retried() -> Result<(), Box<dyn std::error::Error>> {
+        let mut rejected = false;
+        let backend = MockBackend::new(move |cmd| match cmd {
+            ast::Command::DeclareConst(..) if !rejected => {
+                rejected = true;
+                "(error \"declarations are not allowed yet\")".to_string()
+            }
+            _ => "success".to_string(),
+        });
+        let mut solver = Solver::new(backend, false)?;
+        solver.record_transcript(true);
+
+        assert!(solver.declare_const::<Int>("x").is_err());
+        solver.declare_const::<Int>("x")?;
+        assert_eq!(solver.transcript().matches("(declare-const x").count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_sort_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(MockBackend::scripted(["sat"]), false)?;
+        solver.assert(Int::from_name("x").gt(2))?;
+
+        let err = solver.assert(Bool::from_name("x").into()).unwrap_err();
+        assert!(matches!(err, Error::SortMismatch { .. }), "{err}");
+        let err = solver
+            .assert_all([Bool::from_name("y").into(), Int::from_name("y").gt(2)])
+            .unwrap_err();
+        assert!(matches!(err, Error::SortMismatch { .. }), "{err}");
+        // Nothing is remembered from the rejected batch
+        solver.assert(Int::from_name("y").gt(2))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn theory_symbol_names() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(MockBackend::scripted(["sat"]), false)?;
+        solver.record_transcript(true);
+
+        let c = Int::from_name("const");
+        solver.assert(c.gt(2))?;
+        let a = Array::<Int, Int>::from_name("a");
+        solver.assert(a._eq(Array::constant(c)))?;
+        let s = Set::<Int>::from_name("s");
+        solver.assert(s._eq(Set::empty()))?;
+
+        let transcript = solver.transcript();
+        assert_eq!(transcript.matches("(declare-const ").count(), 3);
+        assert!(transcript.contains("(declare-const const Int)"));
+        assert!(!transcript.contains("(declare-const set.empty"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reason_unknown() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .timeout(std::time::Duration::from_millis(1))
+            .build(Z3Binary::new("z3")?)?;
+        solver.set_logic(Logic::QF_NIA)?;
+
+        // No positive solutions exist, but proving so is beyond the solver
+        let [x, y, z] = ["x", "y", "z"].map(Int::from_name);
+        solver.assert(x.gt(0) & y.gt(0) & z.gt(0))?;
+        solver.assert((x.pow(3) + y.pow(3))._eq(z.pow(3)))?;
+
+        assert_eq!(solver.check_sat()?, SatResult::Unknown);
+        let reason = solver.reason_unknown()?;
+        assert!(
+            ["timeout", "canceled", "resource"]
+                .iter()
+                .any(|r| reason.contains(r)),
+            "{reason}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn optimize() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x.ge(3))?;
+        solver.assert(y.le(10))?;
+        solver.minimize(x.into())?;
+        solver.maximize(y.into())?;
+
+        let (res, objectives) = solver.check_sat_with_objectives()?;
+        assert_eq!(res, SatResult::Sat);
+        let objectives = objectives
+            .into_iter()
+            .map(i64::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(objectives, [3, 10]);
+        assert!(solver.transcript().contains("(minimize x)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_soft() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let a = Bool::from_name("a");
+        let b = Bool::from_name("b");
+        solver.assert(!(Bool::from(&a) & &b))?;
+        solver.assert_soft(a.into(), 1)?;
+        solver.assert_soft(b.into(), 2)?;
+
+        let (res, _) = solver.check_sat_with_objectives()?;
+        assert_eq!(res, SatResult::Sat);
+        let model = solver.get_model()?;
+        assert_eq!(model.get("b").unwrap().to_string(), "true");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "z3-static")]
+    #[test]
+    fn z3_static_model() -> Result<(), Box<dyn std::error::Error>> {
+        use smtlib_lowlevel::backend::Z3Static;
+
+        let mut solver = Solver::new(Z3Static::new(&None)?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x._eq(7))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interpolant() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .produce_interpolants(true)
+            .build(Cvc5Binary::new("cvc5")?)?;
+        solver.set_logic(Logic::QF_LIA)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x._eq(&y + 1))?;
+        solver.assert(y.gt(5))?;
+        let interpolant = solver.get_interpolant("I", x.gt(5))?;
+        assert!(!interpolant.to_string().contains('y'));
+
+        // The interpolant follows from the assertions, and implies the
+        // conjecture
+        let mut check = Solver::new(Z3Binary::new("z3")?, false)?;
+        check.assert(x._eq(&y + 1) & y.gt(5) & !interpolant.clone())?;
+        assert_eq!(check.check_sat()?, SatResult::Unsat);
+        let mut check = Solver::new(Z3Binary::new("z3")?, false)?;
+        check.assert(interpolant & x.le(5))?;
+        assert_eq!(check.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fork() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x.gt(5))?;
+        solver.assert(y._eq(&x + 1))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        let mut fork = solver.fork(Z3Binary::new("z3")?)?;
+        fork.assert(y.lt(7))?;
+        solver.assert(y.lt(8))?;
+        assert_eq!(fork.check_sat()?, SatResult::Unsat);
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        assert_eq!(
+            fork.transcript().matches("(declare-const x Int)").count(),
+            1
+        );
+        assert!(!fork.transcript().contains("(assert (< y 8))"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fork_sends_default_options() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = || RecordingBackend::new(MockBackend::new(|_| "success".to_string()));
+        let mut solver = SolverBuilder::new()
+            .record_transcript(true)
+            .build(backend())?;
+        solver.assert(Int::from_name("x").gt(5))?;
+
+        let fork = solver.fork(backend())?;
+        let sent: Vec<_> = fork
+            .driver
+            .backend()
+            .records()
+            .iter()
+            .map(|(cmd, _)| cmd.to_string())
+            .collect();
+        assert!(sent.contains(&"(set-option :produce-assertions true)".to_string()));
+        assert!(sent.contains(&"(declare-const x Int)".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fork_incomplete_transcript() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = || MockBackend::new(|_| "success".to_string());
+        let x = Int::from_name("x");
+
+        let mut cleared = SolverBuilder::new()
+            .record_transcript(true)
+            .build(backend())?;
+        cleared.assert(x.gt(5))?;
+        cleared.clear_transcript();
+        cleared.assert(x.lt(8))?;
+        assert!(matches!(
+            cleared.fork(backend()),
+            Err(Error::TranscriptNotRecorded)
+        ));
+
+        let mut late = Solver::new(backend(), false)?;
+        late.assert(x.gt(5))?;
+        late.record_transcript(true);
+        assert!(matches!(
+            late.fork(backend()),
+            Err(Error::TranscriptNotRecorded)
+        ));
+
+        // Resetting starts a complete transcript again
+        cleared.reset()?;
+        cleared.assert(x.gt(5))?;
+        assert!(cleared.fork(backend()).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_sat_timed() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        let (res, elapsed) = solver.check_sat_timed()?;
+        assert_eq!(res, solver.check_sat()?);
+        assert_eq!(res, SatResult::Sat);
+        assert!(!elapsed.is_zero());
+
+        Ok(())
+    }
+
+    #[test]
+    fn declarations() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Real::from_name("y");
+        let b = Bool::from_name("b");
+        solver.assert(x.gt(2) & y.lt(1.5))?;
+        solver.assert(b.implies(x.lt(5)))?;
+
+        let mut decls: Vec<_> = solver
+            .declarations()
+            .map(|(name, sort)| format!("{name}: {sort}"))
+            .collect();
+        decls.sort();
+        assert_eq!(decls, ["b: Bool", "x: Int", "y: Real"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cached_model() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = RecordingBackend::new(MockBackend::scripted([
+            "sat",
+            "((define-fun x () Int 3))",
+            "sat",
+            "((define-fun x () Int 4))",
+        ]));
+        let mut solver = SolverBuilder::new().cache_models(true).build(backend)?;
+        let get_models = |solver: &Solver<RecordingBackend<MockBackend>>| {
+            let records = solver.driver.backend().records();
+            records
+                .iter()
+                .filter(|(cmd, _)| matches!(cmd, ast::Command::GetModel))
+                .count()
+        };
+
+        let x = Int::from_name("x");
+        solver.assert(x.gt(2))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        let first = solver.get_model()?;
+        let second = solver.get_model()?;
+        assert_eq!(get_models(&solver), 1);
+        assert_eq!(i64::try_from(first.eval(x.clone()).unwrap())?, 3);
+        assert_eq!(i64::try_from(second.eval(x.clone()).unwrap())?, 3);
+
+        solver.assert(x.gt(3))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        let model = solver.get_model()?;
+        assert_eq!(get_models(&solver), 2);
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pop_invalidates_cached_model() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = RecordingBackend::new(MockBackend::scripted([
+            "sat",
+            "((define-fun x () Int 3))",
+            "((define-fun x () Int 3))",
+        ]));
+        let mut solver = SolverBuilder::new().cache_models(true).build(backend)?;
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x.gt(2))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        solver.get_model()?;
+
+        solver.push(1)?;
+        solver.assert(y.gt(x))?;
+        solver.pop(1)?;
+        solver.get_model()?;
+        // `y` was declared in the popped scope, and must be declared again
+        solver.assert(y.gt(2))?;
+
+        let sent = |name: &str| {
+            let records = solver.driver.backend().records();
+            records
+                .iter()
+                .filter(|(cmd, _)| cmd.to_string() == name)
+                .count()
+        };
+        assert_eq!(sent("(get-model)"), 2);
+        assert_eq!(sent("(declare-const y Int)"), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_check_sat() -> Result<(), Box<dyn std::error::Error>> {
+        let mut sat = SatSolver::new(Z3Binary::new("z3")?)?;
+        // NOTE: The pigeonhole principle for 13 pigeons in 12 holes, which
+        // takes far longer to refute than the test waits
+        let (pigeons, holes) = (13, 12);
+        let var = |pigeon: i32, hole: i32| pigeon * holes + hole + 1;
+        for pigeon in 0..pigeons {
+            sat.add_clause(&(0..holes).map(|hole| var(pigeon, hole)).collect::<Vec<_>>())?;
+        }
+        for hole in 0..holes {
+            for a in 0..pigeons {
+                for b in a + 1..pigeons {
+                    sat.add_clause(&[-var(a, hole), -var(b, hole)])?;
+                }
+            }
+        }
+
+        let cancel = AtomicBool::new(false);
+        let res = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                cancel.store(true, Ordering::Release);
+            });
+            sat.solver().check_sat_cancellable(&cancel)
+        })?;
+        assert_eq!(res, SatResult::Unknown);
+
+        let solver = sat.solver();
+        solver.reset()?;
+        solver.assert(Int::from_name("x").gt(2))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        // A check which is cancelled before it starts is never sent
+        solver.record_transcript(true);
+        assert_eq!(solver.check_sat_cancellable(&cancel)?, SatResult::Unknown);
+        assert!(!solver.transcript().contains("(check-sat)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_unknown_logic() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        let res = solver.set_logic(Logic::Custom("NOT_A_LOGIC".to_string()));
+        assert!(res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn set_random_seed() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.set_option(ast::Option::RandomSeed(Numeral("42".to_string())))?;
+        Ok(())
+    }
+
+    #[test]
+    fn builder_sets_options() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = SolverBuilder::new()
+            .produce_unsat_cores(true)
+            .build(Z3Binary::new("z3")?)?;
+
+        let cmd = ast::Command::GetOption(Keyword(":produce-unsat-cores".to_string()));
+        match solver.driver.exec(&cmd)? {
+            ast::GeneralResponse::SpecificSuccessResponse(
+                ast::SpecificSuccessResponse::GetOptionResponse(res),
+            ) => assert_eq!(res.to_string(), "true"),
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn enumerate_models() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let [x, y, z] = ["x", "y", "z"].map(Int::from_name);
+        for v in [&x, &y, &z] {
+            solver.assert(v.in_range(0, 3))?;
+        }
+        solver.assert(distinct([x.clone(), y.clone(), z.clone()]))?;
+
+        let vars = [x.clone().into(), y.clone().into(), z.clone().into()];
+        let mut assignments = vec![];
+        for model in solver.models(&vars) {
+            let model = model?;
+            let value = |v: &Const<Int>| i64::try_from(model.eval(v.clone()).unwrap());
+            assignments.push([value(&x)?, value(&y)?, value(&z)?]);
+        }
+        assignments.sort();
+        assignments.dedup();
+        assert_eq!(assignments.len(), 6);
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn define_sort_alias() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let params = vec![Symbol("X".into())];
+        let x = ast::Sort::Sort(ast::Identifier::Simple(Symbol("X".into())));
+        let definition = ast::Sort::Parametric(
+            ast::Identifier::Simple(Symbol("Array".into())),
+            vec![x.clone(), x],
+        );
+        solver.define_sort("Endo", params, definition)?;
+        let int_endo = ast::Sort::Parametric(
+            ast::Identifier::Simple(Symbol("Endo".into())),
+            vec![Int::sort()],
+        );
+        let f = Dynamic::from_name_with_sort("f", int_endo);
+        let g = Dynamic::from_name_with_sort("g", Array::<Int, Int>::sort());
+        solver.assert(f._eq(g))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        assert!(solver
+            .transcript()
+            .contains("(define-sort Endo (X) (Array X X))"));
+        assert!(solver.transcript().contains("(declare-const f (Endo Int))"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejected_labels_are_forgotten() -> Result<(), Box<dyn std::error::Error>> {
+        let backend = MockBackend::new(|cmd| match cmd {
+            ast::Command::Assert(_) if cmd.to_string().contains(super::LABEL_PREFIX) => {
+                r#"(error "rejected")"#.to_string()
+            }
+            ast::Command::CheckSat => "sat".to_string(),
+            ast::Command::GetModel => "((define-fun x () Int 1))".to_string(),
+            ast::Command::GetValue(_) => r#"(error "unknown label")"#.to_string(),
+            _ => "success".to_string(),
+        });
+        let mut solver = Solver::new(backend, false)?;
+
+        let x = Int::from_name("x");
+        let (_, labeled) = (&x + 1).labeled();
+        assert!(solver.assert(labeled._eq(2)).is_err());
+        assert!(solver.assert_all([x.gt(0), labeled._eq(2)]).is_err());
+        solver.assert(x._eq(1))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 1);
+
+        Ok(())
     }
 }
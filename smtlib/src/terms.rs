@@ -4,18 +4,33 @@
 //! They are a statically-typed and ergonomic layer on top of
 //! [`smtlib_lowlevel::ast::Term`], which provides a more _Rust-like_ API.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use smtlib_lowlevel::{
-    ast::{self, Attribute, AttributeValue, Identifier, QualIdentifier, SortedVar, Term},
+    ast::{
+        self, Attribute, AttributeValue, Identifier, MatchCase, Pattern, QualIdentifier, SortedVar,
+        Term, VarBinding,
+    },
     lexicon::{Keyword, Symbol},
 };
 
-use crate::Bool;
+use crate::{Bool, Error};
 
 pub(crate) fn fun(name: &str, args: Vec<Term>) -> Term {
     Term::Application(qual_ident(name.to_string(), None), args)
 }
+/// The symbol naming a constant called `name`, which is only quoted if
+/// necessary.
+///
+/// Panics if `name` contains `|` or `\`, which cannot occur in symbols.
+pub(crate) fn const_symbol(name: impl Into<String>) -> String {
+    let name = name.into();
+    match Symbol::quoted(&name) {
+        Some(sym) => sym.0,
+        None => panic!("{name:?} is not a valid name, as it contains `|` or `\\`"),
+    }
+}
+
 pub(crate) fn qual_ident(s: String, sort: Option<ast::Sort>) -> QualIdentifier {
     if let Some(sort) = sort {
         QualIdentifier::Sorted(Identifier::Simple(Symbol(s)), sort)
@@ -30,13 +45,17 @@ pub(crate) fn qual_ident(s: String, sort: Option<ast::Sort>) -> QualIdentifier {
 ///
 /// To construct a `Const<T>` call [`T::from_name`](Sort::from_name) where `T`
 /// implements [`Sort`].
-#[derive(Debug, Clone, Copy)]
-pub struct Const<T>(pub(crate) &'static str, pub(crate) T);
+///
+/// Terms are reference counted, so cloning a constant is cheap. Most methods
+/// take `&self`, and operators are implemented for `&Const<T>`, allowing a
+/// constant to be used in several terms without cloning it explicitly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Const<T>(pub(crate) Arc<str>, pub(crate) T);
 
 impl<T> Const<T> {
     /// The name of the constant
     pub fn name(&self) -> &str {
-        self.0
+        &self.0
     }
 }
 impl<T> std::ops::Deref for Const<T> {
@@ -47,36 +66,84 @@ impl<T> std::ops::Deref for Const<T> {
     }
 }
 
-/// This struct wraps specific instances of other terms to indicate that they
-/// are funs. Constants are named terms whose value can be extracted from a
-/// model using [`Model::eval`](crate::Model::eval).
+/// This struct represents a function symbol whose application produces terms
+/// of type `T`. Applying the function to arguments is done with
+/// [`Fun::call`].
 ///
-/// To construct a `Const<T>` call [`T::from_name`](Sort::from_name) where `T`
-/// implements [`Sort`].
-#[derive(Debug, Clone, Copy)]
-pub struct Fun<T>(pub(crate) &'static str, pub(crate) T);
+/// To construct a `Fun<T>` call [`Solver::define_fun`](crate::Solver::define_fun).
+#[derive(Debug, Clone)]
+pub struct Fun<T>(pub(crate) Arc<str>, pub(crate) PhantomData<T>);
 
 impl<T> Fun<T> {
-    /// The name of the constant
+    /// The name of the function
     pub fn name(&self) -> &str {
-        self.0
+        &self.0
     }
-}
-impl<T> std::ops::Deref for Fun<T> {
-    type Target = T;
-
-    fn deref(&self) -> &Self::Target {
-        &self.1
+    /// Construct the term representing `(f ...args)`, that is, the function
+    /// applied to `args`. If `args` is empty, this is just `f`.
+    pub fn call(&self, args: impl IntoIterator<Item = Dynamic>) -> T
+    where
+        T: From<Term>,
+    {
+        let args: Vec<Term> = args.into_iter().map(Into::into).collect();
+        if args.is_empty() {
+            Term::Identifier(qual_ident(self.0.to_string(), None)).into()
+        } else {
+            fun(&self.0, args).into()
+        }
     }
 }
 
 /// This type wraps terms loosing all static type information. It is particular
 /// useful when constructing terms dynamically.
-#[derive(Debug, Clone, Copy)]
-pub struct Dynamic(&'static Term);
+///
+/// The sort of the term is tracked at runtime when known, for example when
+/// converted from a statically typed term or constructed using
+/// [`Dynamic::with_sort`], and is available through [`Dynamic::sort_of`].
+#[derive(Debug, Clone)]
+pub struct Dynamic(Arc<Term>, Option<ast::Sort>);
+// NOTE: The sort is only tracked when known, so it is not considered when
+// comparing terms
+impl PartialEq for Dynamic {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for Dynamic {}
+impl std::hash::Hash for Dynamic {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
 impl std::fmt::Display for Dynamic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        self.0.fmt(f)
+    }
+}
+impl Dynamic {
+    /// Construct a dynamic term from `term` which is known to be of `sort`.
+    pub fn with_sort(term: impl Into<Term>, sort: ast::Sort) -> Self {
+        Dynamic(Arc::new(term.into()), Some(sort))
+    }
+    /// Construct a constant of the given `sort`. Unlike
+    /// [`Dynamic::from_name`](Sort::from_name), the constant is declared with
+    /// `sort` when used in an assertion.
+    ///
+    /// ```
+    /// # use smtlib::{terms::{Dynamic, Sort}, Int};
+    /// let x = Dynamic::from_name_with_sort("x", Int::sort());
+    /// assert_eq!(x.sort_of(), Some(&Int::sort()));
+    /// ```
+    pub fn from_name_with_sort(name: impl Into<String>, sort: ast::Sort) -> Const<Self> {
+        let name = const_symbol(name);
+        Const(
+            name.as_str().into(),
+            Dynamic::with_sort(Term::Identifier(qual_ident(name, Some(sort.clone()))), sort),
+        )
+    }
+    /// The sort of the term, if it is known.
+    pub fn sort_of(&self) -> Option<&ast::Sort> {
+        self.1.as_ref()
     }
 }
 
@@ -85,21 +152,25 @@ impl std::fmt::Display for Dynamic {
 /// This trait indicates that a type can construct a [`Term`] which is the
 /// low-level primitive that is used to define expressions for the SMT solvers
 /// to evaluate.
-pub trait Sort: Into<Term> {
+pub trait Sort: Into<Term> + Clone {
     /// The inner type of the term. This is used for [`Const<T>`](Const) where the inner type is `T`.
     type Inner: Sort;
     /// The sort of the term
     fn sort() -> ast::Sort;
     /// Construct a constant of this sort. See the documentation of [`Const`]
     /// for more information about constants.
+    ///
+    /// The name is quoted as `|name|` in SMT-LIB only if it is not a simple
+    /// symbol, for example if it contains whitespace.
+    ///
+    /// Panics if `name` contains `|` or `\`, which cannot occur in symbols.
     fn from_name(name: impl Into<String>) -> Const<Self>
     where
         Self: From<Term>,
     {
-        // TODO: Only add |_| if necessary
-        let name = format!("|{}|", name.into());
+        let name = const_symbol(name);
         Const(
-            Box::leak(name.clone().into_boxed_str()),
+            name.as_str().into(),
             Term::Identifier(qual_ident(name, Some(Self::sort()))).into(),
         )
     }
@@ -108,15 +179,31 @@ pub trait Sort: Into<Term> {
     where
         Self: From<Term>,
     {
-        d.0.clone().into()
+        Arc::unwrap_or_clone(d.0).into()
+    }
+    /// Casts a dynamically typed term into a concrete type, checking that the
+    /// [sort of the term](Dynamic::sort_of) is the sort of `Self`. If the sort
+    /// of the term is unknown, the cast is unchecked as in
+    /// [`Sort::from_dynamic`].
+    fn try_from_dynamic(d: Dynamic) -> Result<Self, Error>
+    where
+        Self: From<Term>,
+    {
+        match d.sort_of() {
+            Some(sort) if *sort != Self::sort() => Err(Error::SortMismatch {
+                expected: Self::sort().to_string(),
+                actual: sort.to_string(),
+            }),
+            _ => Ok(Self::from_dynamic(d)),
+        }
     }
     /// Construct the term representing `(= self other)`
-    fn _eq(self, other: impl Into<Self::Inner>) -> Bool {
-        fun("=", vec![self.into(), other.into().into()]).into()
+    fn _eq(&self, other: impl Into<Self::Inner>) -> Bool {
+        fun("=", vec![self.clone().into(), other.into().into()]).into()
     }
     /// Construct the term representing `(distinct self other)`
-    fn _neq(self, other: impl Into<Self::Inner>) -> Bool {
-        fun("distinct", vec![self.into(), other.into().into()]).into()
+    fn _neq(&self, other: impl Into<Self::Inner>) -> Bool {
+        fun("distinct", vec![self.clone().into(), other.into().into()]).into()
     }
     /// Wraps the term in a a label, which can be used to extract information
     /// from models at a later point.
@@ -132,7 +219,7 @@ pub trait Sort: Into<Term> {
             Term::Annotation(
                 Box::new(self.into()),
                 vec![Attribute::WithValue(
-                    Keyword("named".to_string()),
+                    Keyword(":named".to_string()),
                     AttributeValue::Symbol(Symbol(name)),
                 )],
             )
@@ -145,6 +232,11 @@ impl<T: Into<Term>> From<Const<T>> for Term {
         c.1.into()
     }
 }
+impl<T: Into<Term> + Clone> From<&Const<T>> for Term {
+    fn from(c: &Const<T>) -> Self {
+        c.1.clone().into()
+    }
+}
 impl<T: Sort> Sort for Const<T> {
     type Inner = T;
     fn sort() -> ast::Sort {
@@ -153,10 +245,11 @@ impl<T: Sort> Sort for Const<T> {
 }
 
 /// Labels are annotations that can be put on expressions to track their
-/// satisfiability.
+/// satisfiability, created using [`Sort::labeled`].
 ///
-/// > **NOTE:** API's for labels are yet to be part of the crate at the time of
-/// > writing.
+/// The value of a labeled term can be retrieved using
+/// [`Solver::value_of_label`](crate::Solver::value_of_label), or from a model
+/// using [`Model::eval_label`](crate::Model::eval_label).
 pub struct Label<T>(u64, PhantomData<T>);
 impl<T> Label<T> {
     pub(crate) fn generate() -> Self {
@@ -168,9 +261,11 @@ impl<T> Label<T> {
         Label(n, PhantomData)
     }
     pub(crate) fn name(&self) -> String {
-        format!("named-label-{}", self.0)
+        format!("{LABEL_PREFIX}{}", self.0)
     }
 }
+/// The prefix of the names of all [`Label`]s.
+pub(crate) const LABEL_PREFIX: &str = "named-label-";
 
 impl<T> From<Const<T>> for Dynamic
 where
@@ -180,14 +275,22 @@ where
         c.1.into()
     }
 }
+impl<T> From<&Const<T>> for Dynamic
+where
+    T: Into<Dynamic> + Clone,
+{
+    fn from(c: &Const<T>) -> Self {
+        c.1.clone().into()
+    }
+}
 impl From<Dynamic> for Term {
     fn from(d: Dynamic) -> Self {
-        d.0.clone()
+        Arc::unwrap_or_clone(d.0)
     }
 }
 impl From<Term> for Dynamic {
     fn from(t: Term) -> Self {
-        Dynamic(Box::leak(Box::new(t)))
+        Dynamic(Arc::new(t), None)
     }
 }
 impl Sort for Dynamic {
@@ -210,6 +313,15 @@ macro_rules! impl_op {
                 self.1.binop($op, rhs.into())
             }
         }
+        impl<R> std::ops::$trait<R> for &Const<$ty>
+        where
+            R: Into<$ty>,
+        {
+            type Output = $ty;
+            fn $fn(self, rhs: R) -> Self::Output {
+                self.1.binop($op, rhs.into())
+            }
+        }
         impl<R> std::ops::$trait<R> for $ty
         where
             R: Into<$ty>,
@@ -236,7 +348,33 @@ macro_rules! impl_op {
             R: Into<$ty>,
         {
             fn $a_fn(&mut self, rhs: R) {
-                *self = *self $a_op rhs;
+                *self = self.clone() $a_op rhs;
+            }
+        }
+    };
+}
+
+/// Implements `Neg` as `(- x)` for a sort, as well as for constants of it.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! impl_neg {
+    ($ty:ty) => {
+        impl std::ops::Neg for $ty {
+            type Output = Self;
+            fn neg(self) -> Self::Output {
+                fun("-", vec![self.into()]).into()
+            }
+        }
+        impl std::ops::Neg for Const<$ty> {
+            type Output = $ty;
+            fn neg(self) -> Self::Output {
+                -self.1
+            }
+        }
+        impl std::ops::Neg for &Const<$ty> {
+            type Output = $ty;
+            fn neg(self) -> Self::Output {
+                -self.1.clone()
             }
         }
     };
@@ -265,10 +403,18 @@ macro_rules! impl_quantifiers {
         {
             fn into_vars(self) -> Vec<SortedVar> {
                 vec![
-                    $(SortedVar(Symbol((self.$n).0.into()), $x::sort())),+
+                    $(SortedVar(Symbol((self.$n).0.to_string()), $x::sort())),+
                 ]
             }
         }
+        impl<$($x,)+> FreshVars for ($(Const<$x>),+)
+        where
+            $($x: Sort + From<Term>),+
+        {
+            fn fresh() -> Self {
+                ($(Const::<$x>::fresh()),+)
+            }
+        }
     };
 }
 impl_quantifiers!(A 0, B 1);
@@ -276,19 +422,58 @@ impl_quantifiers!(A 0, B 1, C 2);
 impl_quantifiers!(A 0, B 1, C 2, D 3);
 impl_quantifiers!(A 0, B 1, C 2, D 3, E 4);
 
-// impl QuantifierVars for Vec<(Const<Dynamic>, ast::Sort)> {
-//     fn into_vars(self) -> Vec<SortedVar> {
-//         self.into_iter()
-//             .map(|(v, s)| SortedVar(Symbol(v.0.into()), s))
-//             .collect()
-//     }
-// }
+impl<A> QuantifierVars for Vec<Const<A>>
+where
+    A: Sort,
+{
+    fn into_vars(self) -> Vec<SortedVar> {
+        self.as_slice().into_vars()
+    }
+}
+impl<A, const N: usize> QuantifierVars for [Const<A>; N]
+where
+    A: Sort,
+{
+    fn into_vars(self) -> Vec<SortedVar> {
+        self.as_slice().into_vars()
+    }
+}
+impl<A> QuantifierVars for &[Const<A>]
+where
+    A: Sort,
+{
+    fn into_vars(self) -> Vec<SortedVar> {
+        self.iter()
+            .map(|c| SortedVar(Symbol(c.0.to_string()), A::sort()))
+            .collect()
+    }
+}
 impl QuantifierVars for Vec<SortedVar> {
     fn into_vars(self) -> Vec<SortedVar> {
         self
     }
 }
 
+/// This trait is implemented for constants and tuples of constants which can
+/// be bound by [`forall_with`] and [`exists_with`].
+pub trait FreshVars: QuantifierVars + Clone {
+    /// Construct constants with names which are not used anywhere else.
+    fn fresh() -> Self;
+}
+
+impl<A> FreshVars for Const<A>
+where
+    A: Sort + From<Term>,
+{
+    fn fresh() -> Self {
+        use core::sync::atomic::{AtomicU64, Ordering};
+        static BOUND_VAR_COUNT: AtomicU64 = AtomicU64::new(0);
+
+        let n = BOUND_VAR_COUNT.fetch_add(1, Ordering::Relaxed);
+        A::from_name(format!("bound-var-{n}"))
+    }
+}
+
 /// Universally quantifies over `vars` in expression `term`.
 pub fn forall(vars: impl QuantifierVars, term: Bool) -> Bool {
     Term::Forall(vars.into_vars(), Box::new(term.into())).into()
@@ -297,3 +482,144 @@ pub fn forall(vars: impl QuantifierVars, term: Bool) -> Bool {
 pub fn exists(vars: impl QuantifierVars, term: Bool) -> Bool {
     Term::Exists(vars.into_vars(), Box::new(term.into())).into()
 }
+/// Universally quantifies over fresh variables in the expression produced by
+/// `body`, which is given the variables as arguments. The sorts of the
+/// variables are determined by the type of the argument of `body`.
+///
+/// ```
+/// # use smtlib::{terms::{forall_with, Const, Sort}, Int};
+/// // forall x. x + 0 = x
+/// let b = forall_with(|x: Const<Int>| (&x + 0)._eq(&x));
+/// // forall x, y. x + y = y + x
+/// let b = forall_with(|(x, y): (Const<Int>, Const<Int>)| (&x + &y)._eq(&y + &x));
+/// ```
+pub fn forall_with<V: FreshVars>(body: impl FnOnce(V) -> Bool) -> Bool {
+    let vars = V::fresh();
+    forall(vars.clone(), body(vars))
+}
+/// Existentially quantifies over fresh variables in the expression produced by
+/// `body`, which is given the variables as arguments. See [`forall_with`] for
+/// more details.
+pub fn exists_with<V: FreshVars>(body: impl FnOnce(V) -> Bool) -> Bool {
+    let vars = V::fresh();
+    exists(vars.clone(), body(vars))
+}
+
+/// Construct the term expressing `(ite cond then otherwise)`, choosing between
+/// `then` and `otherwise` of any sort `T` depending on `cond`. For booleans
+/// see also [`Bool::ite`].
+///
+/// ```
+/// # use smtlib::{terms::{ite, Sort}, Int};
+/// let x = Int::from_name("x");
+/// let sign = ite::<Int>(x.gt(0), 1, -1);
+/// ```
+pub fn ite<T: Sort + From<Term>>(cond: Bool, then: impl Into<T>, otherwise: impl Into<T>) -> T {
+    fun(
+        "ite",
+        vec![cond.into(), then.into().into(), otherwise.into().into()],
+    )
+    .into()
+}
+
+/// The pattern matching values built by the datatype constructor named
+/// `constructor`, binding each of its fields to the variables `vars`. A
+/// constructor without fields is matched by passing no `vars`, which is also
+/// how a single variable matching any value is written.
+///
+/// The bound variables are referred to in the body of the match case by
+/// constants of the same name, which will not be declared by the solver. See
+/// [`match_term`].
+pub fn pattern<'a>(constructor: &str, vars: impl IntoIterator<Item = &'a str>) -> Pattern {
+    let vars: Vec<_> = vars.into_iter().map(|v| Symbol(const_symbol(v))).collect();
+    let constructor = Symbol(constructor.to_string());
+    if vars.is_empty() {
+        Pattern::Symbol(constructor)
+    } else {
+        Pattern::Application(constructor, vars)
+    }
+}
+
+/// Matches `scrutinee` against each of the patterns in `cases` in order,
+/// producing the body of the first case which matches, that is, constructs
+/// the term `(match scrutinee ((pattern body)...))`.
+///
+/// ```
+/// # use smtlib::{terms::{match_term, pattern, Sort}, theories::datatypes::Datatype, Int};
+/// let option = Datatype::new("Option")
+///     .with_constructor("none", [])
+///     .with_constructor("some", [("value", Int::sort())]);
+/// let o = option.from_name("o");
+/// let x = Int::from_name("x");
+/// let or_zero = match_term::<Int>(o, [(pattern("none", []), 0.into()), (pattern("some", ["x"]), x.into())]);
+/// ```
+pub fn match_term<T: Sort + From<Term>>(
+    scrutinee: impl Into<Dynamic>,
+    cases: impl IntoIterator<Item = (Pattern, T)>,
+) -> T {
+    Term::Match(
+        Box::new(scrutinee.into().into()),
+        cases
+            .into_iter()
+            .map(|(p, body)| MatchCase(p, body.into()))
+            .collect(),
+    )
+    .into()
+}
+
+/// Binds each of `bindings` in `body`, that is, constructs the term
+/// `(let ((name term)...) body)`.
+///
+/// The bound names are referred to in `body` by constants of the same name,
+/// which will not be declared by the solver.
+///
+/// ```
+/// # use smtlib::{terms::{let_binding, Sort}, Int};
+/// let x = Int::from_name("x");
+/// let y = Int::from_name("y");
+/// let b = let_binding([("y", (x + 1).into())], y._eq(5));
+/// ```
+pub fn let_binding<T: Sort + From<Term>>(
+    bindings: impl IntoIterator<Item = (impl Into<String>, Dynamic)>,
+    body: T,
+) -> T {
+    Term::Let(
+        bindings
+            .into_iter()
+            .map(|(name, t)| VarBinding(Symbol(const_symbol(name)), t.into()))
+            .collect(),
+        Box::new(body.into()),
+    )
+    .into()
+}
+
+/// Sorts supporting the arithmetic operators `+`, `-` and `*`, namely
+/// [`Int`](crate::Int) and [`Real`](crate::Real).
+pub trait Arithmetic: Sort + From<Term> + From<i64> {}
+
+fn nary<T: Arithmetic>(op: &str, terms: impl IntoIterator<Item = T>, identity: i64) -> T {
+    let mut terms: Vec<Term> = terms.into_iter().map(Into::into).collect();
+    match terms.len() {
+        0 => T::from(identity),
+        1 => terms.pop().unwrap().into(),
+        _ => fun(op, terms).into(),
+    }
+}
+
+/// Construct the term expressing `(+ ...terms)`, the sum of all of `terms` as
+/// a single application rather than nested binary additions. The sum of no
+/// terms is `0`.
+///
+/// ```
+/// # use smtlib::{terms::{sum, Sort}, Int};
+/// let xs = ["x", "y", "z"].map(Int::from_name);
+/// let total: Int = sum(xs.iter().map(Int::from));
+/// ```
+pub fn sum<T: Arithmetic>(terms: impl IntoIterator<Item = T>) -> T {
+    nary("+", terms, 0)
+}
+/// Construct the term expressing `(* ...terms)`, the product of all of
+/// `terms` as a single application. The product of no terms is `1`.
+pub fn product<T: Arithmetic>(terms: impl IntoIterator<Item = T>) -> T {
+    nary("*", terms, 1)
+}
@@ -0,0 +1,130 @@
+#![doc = concat!("```ignore\n", include_str!("./ArraysEx.smt2"), "```")]
+
+use std::{marker::PhantomData, sync::Arc};
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Term},
+    lexicon::Symbol,
+};
+
+use crate::terms::{fun, qual_ident, Const, Dynamic, Sort};
+
+/// An [`Array`] is a term containing a total map from indices of sort `I` to
+/// values of sort `V`. You can [read more
+/// here.](https://smtlib.cs.uiowa.edu/theories-ArraysEx.shtml).
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Array<I, V>(Arc<Term>, PhantomData<(I, V)>);
+
+impl<I, V> Clone for Array<I, V> {
+    fn clone(&self) -> Self {
+        Array(self.0.clone(), PhantomData)
+    }
+}
+impl<I, V> PartialEq for Array<I, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<I, V> Eq for Array<I, V> {}
+impl<I, V> std::hash::Hash for Array<I, V> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<I, V> From<Const<Array<I, V>>> for Array<I, V> {
+    fn from(c: Const<Array<I, V>>) -> Self {
+        c.1
+    }
+}
+impl<I, V> From<&Const<Array<I, V>>> for Array<I, V> {
+    fn from(c: &Const<Array<I, V>>) -> Self {
+        c.1.clone()
+    }
+}
+impl<I, V> std::fmt::Display for Array<I, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<I: Sort, V: Sort> From<Array<I, V>> for Dynamic {
+    fn from(a: Array<I, V>) -> Self {
+        Dynamic::with_sort(a, Array::<I, V>::sort())
+    }
+}
+
+impl<I, V> From<Array<I, V>> for Term {
+    fn from(a: Array<I, V>) -> Self {
+        Arc::unwrap_or_clone(a.0)
+    }
+}
+impl<I, V> From<Term> for Array<I, V> {
+    fn from(t: Term) -> Self {
+        Array(Arc::new(t), PhantomData)
+    }
+}
+impl<I: Sort, V: Sort> Sort for Array<I, V> {
+    type Inner = Self;
+    fn sort() -> ast::Sort {
+        ast::Sort::Parametric(
+            Identifier::Simple(Symbol("Array".into())),
+            vec![I::sort(), V::sort()],
+        )
+    }
+}
+impl<I: Sort, V: Sort + From<Term>> Array<I, V> {
+    /// Construct the term expressing `((as const (Array I V)) value)`, that
+    /// is, the array mapping every index to `value`.
+    pub fn constant(value: impl Into<V>) -> Self {
+        Term::Application(
+            qual_ident("const".into(), Some(Self::sort())),
+            vec![value.into().into()],
+        )
+        .into()
+    }
+    /// Construct the term expressing `(select self index)`, that is, the value
+    /// of `self` at `index`.
+    pub fn select(&self, index: impl Into<I>) -> V {
+        fun("select", vec![self.clone().into(), index.into().into()]).into()
+    }
+    /// Construct the term expressing `(store self index value)`, that is, the
+    /// array which is `value` at `index` and otherwise the same as `self`.
+    pub fn store(&self, index: impl Into<I>, value: impl Into<V>) -> Self {
+        fun(
+            "store",
+            vec![
+                self.clone().into(),
+                index.into().into(),
+                value.into().into(),
+            ],
+        )
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{terms::Sort, Int, SatResult, Solver};
+
+    use super::Array;
+
+    #[test]
+    fn store_into_constant() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let a = Array::<Int, Int>::from_name("a");
+        solver.assert(a._eq(Array::constant(0).store(5, 7)))?;
+        solver.assert(a.select(5)._neq(7) | a.select(6)._neq(0))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+}
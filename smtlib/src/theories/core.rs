@@ -1,19 +1,27 @@
 #![doc = concat!("```ignore\n", include_str!("./Core.smt2"), "```")]
 
+use std::sync::Arc;
+
 use smtlib_lowlevel::{
-    ast::{self, Identifier, Term},
+    ast::{self, Identifier, QualIdentifier, Term},
     lexicon::Symbol,
 };
 
 use crate::{
     impl_op,
-    terms::{fun, qual_ident, Const, Dynamic, Sort},
+    terms::{fun, ite, qual_ident, sum, Const, Dynamic, Sort},
+    Error, Int,
 };
 
 /// A [`Bool`] is a term containing a
 /// [boolean](https://en.wikipedia.org/wiki/Boolean_data_type). You can [read more
 /// here.](https://smtlib.cs.uiowa.edu/theories-Core.shtml).
-#[derive(Clone, Copy)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
 pub struct Bool(BoolImpl);
 
 impl std::fmt::Debug for Bool {
@@ -22,25 +30,30 @@ impl std::fmt::Debug for Bool {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum BoolImpl {
     #[allow(unused)]
-    Const(&'static str),
-    Term(&'static Term),
+    Const(Arc<str>),
+    Term(Arc<Term>),
 }
 impl From<Const<Bool>> for Bool {
     fn from(c: Const<Bool>) -> Self {
         c.1
     }
 }
+impl From<&Const<Bool>> for Bool {
+    fn from(c: &Const<Bool>) -> Self {
+        c.1.clone()
+    }
+}
 impl std::fmt::Display for Bool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        Term::from(self.clone()).fmt(f)
     }
 }
 impl From<Bool> for Dynamic {
     fn from(b: Bool) -> Self {
-        Term::from(b).into()
+        Dynamic::with_sort(b, Bool::sort())
     }
 }
 impl From<bool> for Bool {
@@ -56,14 +69,102 @@ impl From<bool> for Bool {
 impl From<Bool> for Term {
     fn from(b: Bool) -> Self {
         match b.0 {
-            BoolImpl::Const(name) => Term::Identifier(qual_ident(name.into(), None)),
-            BoolImpl::Term(t) => t.clone(),
+            BoolImpl::Const(name) => Term::Identifier(qual_ident(name.to_string(), None)),
+            BoolImpl::Term(t) => Arc::unwrap_or_clone(t),
         }
     }
 }
 impl From<Term> for Bool {
     fn from(t: Term) -> Self {
-        Bool(BoolImpl::Term(Box::leak(Box::new(t))))
+        Bool(BoolImpl::Term(Arc::new(t)))
+    }
+}
+impl TryFrom<Bool> for bool {
+    type Error = Error;
+
+    fn try_from(value: Bool) -> Result<Self, Self::Error> {
+        let term = Term::from(value);
+        bool_value(&term).ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a boolean literal",
+            term: term.to_string(),
+        })
+    }
+}
+fn bool_value(t: &Term) -> Option<bool> {
+    match t {
+        Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(Symbol(s)))) => {
+            match s.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+/// Simplifies `term` if `positive`, and its negation otherwise. Negations are
+/// pushed inward through `not`, `and`, `or`, and `=>` until they reach atoms.
+fn simplify(term: Term, positive: bool) -> Term {
+    if let Some(b) = bool_value(&term) {
+        return Bool::from(b == positive).into();
+    }
+    let (op, args) = match term {
+        Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args) => (op, args),
+        term if positive => return term,
+        term => return fun("not", vec![term]),
+    };
+    match (op.0.as_str(), args.len()) {
+        ("not", 1) => simplify(args.into_iter().next().unwrap(), !positive),
+        ("and" | "or", _) => {
+            // NOTE: By De Morgan's laws the negation of a conjunction is the
+            // disjunction of the negations, and vice versa
+            let is_and = (op.0 == "and") == positive;
+            junction(is_and, args.into_iter().map(|arg| simplify(arg, positive)))
+        }
+        // `(=> a b c)` is `(or (not a) (not b) c)`
+        ("=>", n) if n >= 2 => {
+            let args = args.into_iter().enumerate().map(|(i, arg)| {
+                let premise = i + 1 < n;
+                simplify(arg, premise != positive)
+            });
+            junction(!positive, args)
+        }
+        _ => {
+            let term = Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args);
+            if positive {
+                term
+            } else {
+                fun("not", vec![term])
+            }
+        }
+    }
+}
+/// Constructs the conjunction if `is_and`, and the disjunction otherwise, of
+/// the already simplified `args`, folding `true` and `false` and flattening
+/// nested junctions of the same kind.
+fn junction(is_and: bool, args: impl IntoIterator<Item = Term>) -> Term {
+    let op = if is_and { "and" } else { "or" };
+    let mut flat = vec![];
+    for arg in args {
+        match bool_value(&arg) {
+            // `true` for `and` and `false` for `or` have no effect
+            Some(b) if b == is_and => continue,
+            Some(b) => return Bool::from(b).into(),
+            None => {}
+        }
+        match arg {
+            Term::Application(QualIdentifier::Identifier(Identifier::Simple(o)), inner)
+                if o.0 == op =>
+            {
+                flat.extend(inner)
+            }
+            arg => flat.push(arg),
+        }
+    }
+    match flat.len() {
+        0 => Bool::from(is_and).into(),
+        1 => flat.pop().unwrap(),
+        _ => fun(op, flat),
     }
 }
 impl Sort for Bool {
@@ -73,15 +174,15 @@ impl Sort for Bool {
     }
 }
 impl Bool {
-    fn binop(self, op: &str, other: Bool) -> Self {
-        fun(op, vec![self.into(), other.into()]).into()
+    fn binop(&self, op: &str, other: Bool) -> Self {
+        fun(op, vec![self.clone().into(), other.into()]).into()
     }
     /// Construct the term expressing `(==> self other)`.
     ///
     /// The value of the returned boolean is true if:
     /// - `self` is false
     /// - or `other` is true
-    pub fn implies(self, other: Bool) -> Bool {
+    pub fn implies(&self, other: Bool) -> Bool {
         self.binop("=>", other)
     }
     /// Construct the term expressing `(ite self then otherwise)`.
@@ -91,8 +192,44 @@ impl Bool {
     /// and an if statement:
     /// - **C-style notation:** `self ? then : otherwise`
     /// - **Rust notation:**  `if self { then } else { otherwise }`
-    pub fn ite(self, then: Bool, otherwise: Bool) -> Bool {
-        fun("ite", vec![self.into(), then.into(), otherwise.into()]).into()
+    pub fn ite(&self, then: Bool, otherwise: Bool) -> Bool {
+        fun(
+            "ite",
+            vec![self.clone().into(), then.into(), otherwise.into()],
+        )
+        .into()
+    }
+    /// Construct the term expressing `(ite self then otherwise)` for integers,
+    /// that is, `then` if `self` is true, and `otherwise` if not. See also
+    /// [`ite`] for other sorts.
+    pub fn if_then_else_int(&self, then: impl Into<Int>, otherwise: impl Into<Int>) -> Int {
+        ite(self.clone(), then, otherwise)
+    }
+    /// Construct the term expressing `(ite self 1 0)`, that is, `1` if `self`
+    /// is true, and `0` if not. Summing these counts the number of true terms.
+    pub fn to_int(&self) -> Int {
+        self.if_then_else_int(1, 0)
+    }
+    /// Simplifies the boolean structure of the term, which is useful to
+    /// reduce the size of large generated formulas. The rewrites are:
+    /// - Negations are pushed inward through `and`, `or`, and `=>`, and
+    ///   double negations are removed, such that `not` is only applied to
+    ///   atoms. This is also known as negation normal form.
+    /// - `true` and `false` are folded, such as `(and x false)` to `false`.
+    /// - `and` and `or` of a single argument are replaced by the argument,
+    ///   and nested ones of the same kind are flattened.
+    ///
+    /// Atoms, such as comparisons and applications of other functions, are
+    /// left as they are, including any boolean terms within them.
+    ///
+    /// ```
+    /// # use smtlib::{Bool, Sort};
+    /// let [x, y] = ["x", "y"].map(Bool::from_name);
+    /// let b = !(Bool::from(&x) & (!Bool::from(&y) | false));
+    /// assert_eq!(b.simplify().to_string(), "(or (not (as x Bool)) (as y Bool))");
+    /// ```
+    pub fn simplify(self) -> Bool {
+        simplify(self.into(), true).into()
     }
 }
 
@@ -141,3 +278,147 @@ where
 {
     fun("distinct", terms.map(Into::into).to_vec()).into()
 }
+
+/// The number of `bs` which are true, as the sum of `(ite b 1 0)` for each.
+fn count_true(bs: &[Bool]) -> Int {
+    sum(bs.iter().map(Bool::to_int))
+}
+/// Construct the term expressing that at most `k` of `bs` are true.
+///
+/// The constraint is encoded by counting the true terms using integer
+/// arithmetic, rather than the non-standard `(_ at-most k)` of z3, and thus
+/// works with any solver supporting linear integer arithmetic.
+pub fn at_most(k: usize, bs: &[Bool]) -> Bool {
+    count_true(bs).le(k as i64)
+}
+/// Construct the term expressing that at least `k` of `bs` are true. See
+/// [`at_most`] for how it is encoded.
+pub fn at_least(k: usize, bs: &[Bool]) -> Bool {
+    count_true(bs).ge(k as i64)
+}
+/// Construct the term expressing that exactly `k` of `bs` are true. See
+/// [`at_most`] for how it is encoded.
+pub fn exactly(k: usize, bs: &[Bool]) -> Bool {
+    count_true(bs)._eq(k as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::{ast::Term, backend::Z3Binary};
+
+    use crate::{
+        terms::{sum, Sort},
+        Int, SatResult, Solver,
+    };
+
+    use super::{and, at_least, at_most, exactly, Bool};
+
+    #[test]
+    fn bool_from_model() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let a = Bool::from_name("a");
+        let b = Bool::from_name("b");
+        solver.assert(&a ^ &b)?;
+        solver.assert(b._eq(false))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert!(bool::try_from(model.eval(a).unwrap())?);
+        assert!(!bool::try_from(model.eval(b).unwrap())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cardinality() -> Result<(), Box<dyn std::error::Error>> {
+        let bs: Vec<Bool> = (0..5)
+            .map(|i| Bool::from_name(format!("b{i}")).into())
+            .collect();
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(at_most(2, &bs))?;
+        solver.assert(and([bs[0].clone(), bs[2].clone()]))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        solver.assert(bs[4].clone())?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(at_least(4, &bs))?;
+        solver.assert(exactly(4, &bs))?;
+        solver.assert(!bs[1].clone())?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        for (i, b) in (0..5).map(|i| Bool::from_name(format!("b{i}"))).enumerate() {
+            assert_eq!(bool::try_from(model.eval(b).unwrap())?, i != 1);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_int() -> Result<(), Box<dyn std::error::Error>> {
+        let bs: Vec<Bool> = (0..4)
+            .map(|i| Bool::from_name(format!("b{i}")).into())
+            .collect();
+        assert_eq!(bs[0].to_int().to_string(), "(ite (as b0 Bool) 1 0)");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(sum(bs.iter().map(Bool::to_int))._eq(3))?;
+        solver.assert(!bs[2].clone())?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        for (i, b) in (0..4).map(|i| Bool::from_name(format!("b{i}"))).enumerate() {
+            assert_eq!(bool::try_from(model.eval(b).unwrap())?, i != 2);
+        }
+
+        let score = bs[1].if_then_else_int(10, 5);
+        assert_eq!(score.to_string(), "(ite (as b1 Bool) 10 5)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn simplify() {
+        let x = Bool::from_name("x");
+        let y = Bool::from_name("y");
+        let n = Int::from_name("n");
+
+        assert_eq!(
+            Term::from((!!Bool::from(&x)).simplify()),
+            Term::from(x.clone())
+        );
+        assert_eq!(
+            Term::from(and([x.clone().into()]).simplify()),
+            Term::from(x.clone())
+        );
+        assert_eq!(
+            (!(&x & !Bool::from(&y))).simplify().to_string(),
+            "(or (not (as x Bool)) (as y Bool))"
+        );
+        assert_eq!(
+            (!x.implies(n.gt(2))).simplify().to_string(),
+            "(and (as x Bool) (not (> (as n Int) 2)))"
+        );
+        assert_eq!(
+            ((&x | &y) | (Bool::from(&x) & true)).simplify().to_string(),
+            "(or (as x Bool) (as y Bool) (as x Bool))"
+        );
+        assert_eq!((&x & false).simplify().to_string(), "false");
+        assert_eq!((!(&x | true)).simplify().to_string(), "false");
+        assert_eq!(and([]).simplify().to_string(), "true");
+    }
+
+    #[test]
+    fn serde_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+        let b = Bool::from_name("b");
+        let term = b.implies(x.gt(3) & x.lt(Int::from(10) * 2));
+
+        let json = serde_json::to_string(&term)?;
+        let back: Bool = serde_json::from_str(&json)?;
+        assert_eq!(Term::from(back), Term::from(term));
+
+        let x: Int = serde_json::from_str(&serde_json::to_string(&(x + 1))?)?;
+        assert_eq!(x.to_string(), "(+ (as x Int) 1)");
+
+        Ok(())
+    }
+}
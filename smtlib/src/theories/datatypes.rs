@@ -0,0 +1,195 @@
+//! Algebraic datatypes, such as pairs, lists and trees. You can [read more
+//! here](https://smtlib.cs.uiowa.edu/papers/smt-lib-reference-v2.6-r2021-05-12.pdf),
+//! in section 4.2.3 of the SMT-LIB reference.
+//!
+//! A [`Datatype`] describes the constructors of the datatype and their
+//! fields, and is declared using
+//! [`Solver::declare_datatype`](crate::Solver::declare_datatype). Once
+//! declared, terms of the datatype are constructed using
+//! [`Datatype::constructor`] and taken apart using [`Datatype::selector`].
+
+use std::marker::PhantomData;
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Term},
+    lexicon::{Numeral, Symbol},
+};
+
+use crate::terms::{const_symbol, qual_ident, Const, Dynamic, Fun};
+
+/// The description of an algebraic datatype, consisting of a name and a
+/// sequence of constructors each with a number of named fields.
+///
+/// ```
+/// # use smtlib::{terms::Sort, theories::datatypes::Datatype, Int};
+/// let pair = Datatype::new("Pair").with_constructor(
+///     "mk-pair",
+///     [("first", Int::sort()), ("second", Int::sort())],
+/// );
+/// // Recursive datatypes refer to themselves using `Datatype::sort_of`
+/// let list = Datatype::new("List")
+///     .with_constructor("nil", [])
+///     .with_constructor(
+///         "cons",
+///         [("head", Int::sort()), ("tail", Datatype::sort_of("List"))],
+///     );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Datatype {
+    name: String,
+    constructors: Vec<ast::ConstructorDec>,
+}
+
+impl Datatype {
+    /// Starts the description of a datatype named `name` with no
+    /// constructors. At least one constructor must be added using
+    /// [`Datatype::with_constructor`] before it can be declared.
+    pub fn new(name: impl Into<String>) -> Self {
+        Datatype {
+            name: name.into(),
+            constructors: vec![],
+        }
+    }
+    /// Adds a constructor named `name` with the given named `fields`. The
+    /// names of the fields are the selectors, which extract the fields from
+    /// values built by the constructor.
+    pub fn with_constructor<'a>(
+        mut self,
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = (&'a str, ast::Sort)>,
+    ) -> Self {
+        self.constructors.push(ast::ConstructorDec(
+            Symbol(name.into()),
+            fields
+                .into_iter()
+                .map(|(s, sort)| ast::SelectorDec(Symbol(s.to_string()), sort))
+                .collect(),
+        ));
+        self
+    }
+    /// The name of the datatype
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The sort of a datatype named `name`. This is useful to refer to the
+    /// datatype in the fields of its own constructors.
+    pub fn sort_of(name: impl Into<String>) -> ast::Sort {
+        ast::Sort::Sort(Identifier::Simple(Symbol(name.into())))
+    }
+    /// The sort of the datatype
+    pub fn sort(&self) -> ast::Sort {
+        Self::sort_of(self.name.clone())
+    }
+    /// Construct a constant of the datatype. See the documentation of
+    /// [`Const`] for more information about constants.
+    pub fn from_name(&self, name: impl Into<String>) -> Const<Dynamic> {
+        let name = const_symbol(name);
+        Const(
+            name.as_str().into(),
+            Dynamic::with_sort(
+                Term::Identifier(qual_ident(name, Some(self.sort()))),
+                self.sort(),
+            ),
+        )
+    }
+    /// The constructor named `name`, which when called with values for each
+    /// of its fields produces a value of the datatype.
+    ///
+    /// Panics if the datatype has no such constructor.
+    pub fn constructor(&self, name: &str) -> Fun<Dynamic> {
+        let c = self
+            .constructors
+            .iter()
+            .find(|c| c.0 .0 == name)
+            .unwrap_or_else(|| panic!("{} has no constructor named {name}", self.name));
+        Fun(c.0 .0.as_str().into(), PhantomData)
+    }
+    /// The selector named `name`, which extracts the field of that name from a
+    /// value of the datatype. `T` is the sort of the field.
+    ///
+    /// Panics if the datatype has no such selector.
+    pub fn selector<T>(&self, name: &str) -> Fun<T> {
+        let s = self
+            .constructors
+            .iter()
+            .flat_map(|c| &c.1)
+            .find(|s| s.0 .0 == name)
+            .unwrap_or_else(|| panic!("{} has no selector named {name}", self.name));
+        Fun(s.0 .0.as_str().into(), PhantomData)
+    }
+    /// The command declaring the datatype
+    pub(crate) fn declaration(&self) -> ast::Command {
+        ast::Command::DeclareDatatypes(
+            vec![ast::SortDec(
+                Symbol(self.name.clone()),
+                Numeral("0".to_string()),
+            )],
+            vec![ast::DatatypeDec::DatatypeDec(self.constructors.clone())],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{
+        terms::{match_term, pattern, Sort},
+        Int, SatResult, Solver,
+    };
+
+    use super::Datatype;
+
+    #[test]
+    fn pair() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let pair = Datatype::new("Pair")
+            .with_constructor("mk-pair", [("first", Int::sort()), ("second", Int::sort())]);
+        solver.declare_datatype(&pair)?;
+
+        let mk_pair = pair.constructor("mk-pair");
+        let first = pair.selector::<Int>("first");
+        let p = pair.from_name("p");
+
+        solver.assert(p._eq(mk_pair.call([Int::from(1).into(), Int::from(2).into()])))?;
+        solver.assert(first.call([p.into()])._neq(1))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn match_option() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let option = Datatype::new("Option")
+            .with_constructor("none", [])
+            .with_constructor("some", [("value", Int::sort())]);
+        solver.declare_datatype(&option)?;
+
+        let o = option.from_name("o");
+        let x = Int::from_name("x");
+        let or_zero = match_term::<Int>(
+            &o,
+            [
+                (pattern("none", []), Int::from(0)),
+                (pattern("some", ["x"]), x + 1),
+            ],
+        );
+        assert_eq!(
+            or_zero.to_string(),
+            "(match (as o Option) ((none 0) ((some x) (+ (as x Int) 1))))"
+        );
+
+        solver.assert(o._neq(option.constructor("none").call([])))?;
+        solver.assert(or_zero._eq(5))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.get("o").unwrap().to_string(), "(some 4)");
+        // The pattern variable is bound, and thus not declared
+        assert!(!solver.transcript().contains("(declare-const x"));
+
+        Ok(())
+    }
+}
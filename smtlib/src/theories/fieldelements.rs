@@ -1,57 +1,79 @@
 #![doc = concat!("```ignore\n", include_str!("./FieldElements.smt2"), "```")]
 
+use std::sync::Arc;
+
 use smtlib_lowlevel::{
-    ast::{self, Identifier, Term},
-    lexicon::Symbol,
+    ast::{self, Identifier, Index, QualIdentifier, SpecConstant, Term},
+    lexicon::{Numeral, Symbol},
 };
 
 use crate::{
     impl_op,
     terms::{fun, qual_ident, Const, Dynamic, Sort},
+    Bool, Error,
 };
 
 use num_bigint::BigUint;
-use regex::Regex;
 use num_traits::Num;
 
 /// A [`FieldElement`] is a term containing a
 /// [finite field element](https://mathworld.wolfram.com/FiniteField.html). You can [read more
 /// here.](https://docs.circom.io/background/background/#arithmetic-circuits).
-#[derive(Debug, Clone, Copy)]
-pub struct FieldElement(&'static Term);
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct FieldElement(Arc<Term>);
 impl From<Const<FieldElement>> for FieldElement {
     fn from(c: Const<FieldElement>) -> Self {
         c.1
     }
 }
+impl From<&Const<FieldElement>> for FieldElement {
+    fn from(c: &Const<FieldElement>) -> Self {
+        c.1.clone()
+    }
+}
 impl std::fmt::Display for FieldElement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        self.0.fmt(f)
     }
 }
 
 impl From<FieldElement> for Dynamic {
     fn from(i: FieldElement) -> Self {
-        Term::from(i).into()
+        Dynamic::with_sort(i, FieldElement::sort())
     }
 }
 
 impl PartialEq for FieldElement {
     fn eq(&self, other: &Self) -> bool {
-        let self_string = self.to_biguint();
-        let other_string = other.to_biguint();
-        self_string == other_string
+        match (self.to_biguint(), other.to_biguint()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => self.0 == other.0,
+        }
+    }
+}
+impl Eq for FieldElement {}
+impl std::hash::Hash for FieldElement {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        match self.to_biguint() {
+            Ok(value) => value.hash(state),
+            Err(_) => self.0.hash(state),
+        }
     }
 }
 
 impl From<FieldElement> for Term {
     fn from(i: FieldElement) -> Self {
-        i.0.clone()
+        Arc::unwrap_or_clone(i.0)
     }
 }
 impl From<Term> for FieldElement {
     fn from(t: Term) -> Self {
-        FieldElement(Box::leak(Box::new(t)))
+        FieldElement(Arc::new(t))
     }
 }
 impl Sort for FieldElement {
@@ -71,20 +93,58 @@ impl From<BigUint> for FieldElement {
     }
 }
 impl FieldElement {
-    fn binop<T: From<Term>>(self, op: &str, other: FieldElement) -> T {
-        fun(op, vec![self.into(), other.into()]).into()
+    /// The sort of the finite field of the given `order`, that is,
+    /// `(_ FiniteField order)`. The sort `F` of field elements is defined as
+    /// this sort by [`Solver::set_field_order`](crate::Solver::set_field_order).
+    pub fn field_sort(order: &BigUint) -> ast::Sort {
+        ast::Sort::Sort(Identifier::Indexed(
+            Symbol("FiniteField".to_string()),
+            vec![Index::Numeral(Numeral(order.to_string()))],
+        ))
+    }
+    fn binop<T: From<Term>>(&self, op: &str, other: FieldElement) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
     }
 
-    /// Turn a FieldElement into a BigUint
-    pub fn to_biguint(&self) -> BigUint {
-        // Remove irrelevant parts using a regex to match BigUints
-        let re = Regex::new(r"[0-9]+").unwrap();
-        let s = self.to_string();
-        let relevant_part = re.find(&s.as_str()).unwrap().as_str();
-        
-        // Convert to BigUint
-        let big_u = BigUint::from_str_radix(relevant_part, 10).expect("Invalid number");
-        big_u
+    /// Turn a FieldElement into a BigUint. This only succeeds for literals,
+    /// that is, either `(as ffN F)` or `#fNmM` as produced in models, and
+    /// fails for compound terms such as `(ff.neg ff3 F)`.
+    pub fn to_biguint(&self) -> Result<BigUint, Error> {
+        let value = match &*self.0 {
+            Term::SpecConstant(SpecConstant::Fieldelement(f)) => f.parse().ok(),
+            Term::Identifier(QualIdentifier::Identifier(Identifier::Simple(Symbol(s)))) => s
+                .strip_prefix("(as ff")
+                .and_then(|s| s.strip_suffix(" F)"))
+                .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| BigUint::from_str_radix(n, 10).ok()),
+            _ => None,
+        };
+        value.ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a field element literal",
+            term: self.to_string(),
+        })
+    }
+
+    /// Construct a constant named `name` for the multiplicative inverse of
+    /// `self`, along with the constraint `(= (ff.mul self name) 1)` defining
+    /// it. The constraint must be asserted for the constant to be the inverse.
+    ///
+    /// Since zero has no inverse, the constraint is unsatisfiable if `self` is
+    /// zero.
+    pub fn inverse(&self, name: impl Into<String>) -> (Const<FieldElement>, Bool) {
+        let inv = FieldElement::from_name(name);
+        let constraint = (self.clone() * &inv)._eq(1);
+        (inv, constraint)
+    }
+
+    /// Construct the term expressing `self` raised to the power of `exp`, as
+    /// `exp` factors of `self` multiplied using `ff.mul`.
+    pub fn pow(&self, exp: u64) -> FieldElement {
+        match exp {
+            0 => FieldElement::from(1),
+            1 => self.clone(),
+            _ => fun("ff.mul", (0..exp).map(|_| self.clone().into()).collect()).into(),
+        }
     }
 }
 
@@ -101,9 +161,9 @@ impl_op!(FieldElement, i64, Mul, mul, "ff.mul", MulAssign, mul_assign, *);
 #[cfg(test)]
 mod tests {
     use num_bigint::BigUint;
-    use smtlib_lowlevel::backend::Cvc5Binary;
+    use smtlib_lowlevel::{ast, backend::Cvc5Binary, lexicon::Symbol};
 
-    use crate::{terms::Sort, Solver, SatResult};
+    use crate::{solver::declarations, terms::Sort, Solver, SatResult};
 
     use super::FieldElement;
     use std::ops::{Mul, Neg, Add};
@@ -128,23 +188,19 @@ mod tests {
         let two = FieldElement::from(2);
 
         // SAT
-        solver.assert(a.mul(b)._eq(one))?;
-        solver.assert(a._eq(two))?;
+        solver.assert((&a * &b)._eq(one))?;
+        solver.assert(a._eq(two.clone()))?;
         
         // let model = solver.check_sat_with_model()?.expect_sat()?;
         let sat_result = solver.check_sat()?;
         println!("Debug sat {:?}", sat_result);
-        let sat_string = format!("{:?}", sat_result);
-        let sat_expected = format!("{:?}", SatResult::Sat);
-        assert!(sat_expected == sat_string);    
+        assert_eq!(sat_result, SatResult::Sat);
     
         // Now, assert for UnSat
         solver.assert(b._eq(two))?;
         let sat_result = solver.check_sat()?;
         println!("Debug sat {:?}", sat_result);
-        let sat_string = format!("{:?}", sat_result);
-        let sat_expected = format!("{:?}", SatResult::Unsat);
-        assert!(sat_expected == sat_string);  
+        assert_eq!(sat_result, SatResult::Unsat);
         
         Ok(())
     }
@@ -169,19 +225,19 @@ mod tests {
         let two = FieldElement::from(2);
         let three = FieldElement::from(3);
 
-        solver.assert(one.neg()._eq(FieldElement::from(4)))?; 
+        solver.assert(one.clone().neg()._eq(FieldElement::from(4)))?; 
 
         // 1 * 2 + 2 * 2 = 1 * 1
-        solver.assert(one.mul(two).add(two.mul(two))._eq(one.mul(one)))?;
+        solver.assert(
+            one.clone().mul(two.clone()).add(two.clone().mul(two.clone()))._eq(one.clone().mul(one.clone())),
+        )?;
 
         // 1 * a + 2 * b - 3 * c = 0
         solver.assert(one.mul(a).add(two.mul(b)).add(FieldElement::neg(three.mul(c)))._eq(FieldElement::from(0)))?;
 
         let sat_result = solver.check_sat()?;
         println!("Debug sat {:?}", sat_result);
-        let sat_string = format!("{:?}", sat_result);
-        let sat_expected = format!("{:?}", SatResult::Sat);
-        assert!(sat_expected == sat_string); 
+        assert_eq!(sat_result, SatResult::Sat);
 
         // Test getmodel
         let model = solver.get_model()?;
@@ -208,9 +264,7 @@ mod tests {
 
         let sat_result = solver.check_sat()?;
         println!("Debug Sat {:?}", sat_result);
-        let sat_string = format!("{:?}", sat_result);
-        let sat_expected = format!("{:?}", SatResult::Sat);
-        assert!(sat_expected == sat_string);
+        assert_eq!(sat_result, SatResult::Sat);
         Ok(())
     }
 
@@ -228,10 +282,10 @@ mod tests {
 
         let a = FieldElement::from(BigUint::from(5u32));
         let b = FieldElement::from(BigUint::from(5u32));
-        solver.assert(a._eq(b))?;
+        solver.assert(a._eq(b.clone()))?;
 
-        let parsed_a = FieldElement::to_biguint(&a);
-        let parsed_b = FieldElement::to_biguint(&b);
+        let parsed_a = FieldElement::to_biguint(&a)?;
+        let parsed_b = FieldElement::to_biguint(&b)?;
 
         println!("Debug parsed_a {:?}", parsed_a);
         println!("Debug parsed_b {:?}", parsed_b);
@@ -239,9 +293,52 @@ mod tests {
 
         let sat_result = solver.check_sat()?;
         println!("Debug Sat {:?}", sat_result);
-        let sat_string = format!("{:?}", sat_result);
-        let sat_expected = format!("{:?}", SatResult::Sat);
-        assert!(sat_expected == sat_string);
+        assert_eq!(sat_result, SatResult::Sat);
+        Ok(())
+    }
+    #[test]
+    fn to_biguint_only_accepts_literals() {
+        assert_eq!(FieldElement::from(3).to_biguint().unwrap(), BigUint::from(3u32));
+        assert!(FieldElement::from(3).neg().to_biguint().is_err());
+        assert!(FieldElement::from(12).add(3).to_biguint().is_err());
+        assert!(FieldElement::from(12) != FieldElement::from(12).add(0));
+    }
+
+    #[test]
+    fn field_sort_round_trip() {
+        let define = ast::Command::DefineSort(
+            Symbol("F".to_string()),
+            vec![],
+            FieldElement::field_sort(&BigUint::from(5u32)),
+        );
+        assert_eq!(define.to_string(), "(define-sort F () (_ FiniteField 5))");
+
+        let a = FieldElement::from_name("a");
+        let term = ast::Term::from(a._eq(1));
+        let declarations =
+            declarations(&mut Default::default(), &mut Default::default(), &term, &[]).unwrap();
+        assert_eq!(declarations.len(), 1);
+        for cmd in [define].into_iter().chain(declarations) {
+            assert_eq!(ast::Command::parse(&cmd.to_string()).unwrap(), cmd);
+        }
+    }
+
+    #[test]
+    fn inverse_and_pow() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Cvc5Binary::new("src/theories/cvc5")?, false)?;
+        solver.set_logic(crate::Logic::QF_FF)?;
+        solver.set_field_order(&BigUint::from(5u32))?;
+
+        // 2 * 3 = 6 = 1 (mod 5)
+        let (inv, constraint) = FieldElement::from(2).inverse("inv");
+        solver.assert(constraint)?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(inv).unwrap().to_biguint()?, BigUint::from(3u32));
+
+        // 2^3 = 8 = 3 (mod 5)
+        solver.assert(FieldElement::from(2).pow(3)._neq(3))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
         Ok(())
     }
 }
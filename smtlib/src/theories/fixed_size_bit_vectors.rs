@@ -1,44 +1,59 @@
 #![doc = concat!("```ignore\n", include_str!("./FixedSizeBitVectors.smt2"), "```")]
 
+use std::sync::Arc;
+
 use itertools::Itertools;
 use smtlib_lowlevel::{
     ast::{self, Identifier, Index, Term},
-    lexicon::{Numeral, Symbol},
+    lexicon::{Binary, Hexadecimal, Numeral, Symbol},
 };
 
-use crate::terms::{fun, qual_ident, Const, Dynamic, Sort};
+use crate::{
+    terms::{const_symbol, fun, qual_ident, Const, Dynamic, Sort},
+    Bool, Error, Int,
+};
 
 /// A bit-vec is a fixed size sequence of boolean values. You can [read more
 /// about it
 /// here](https://smtlib.cs.uiowa.edu/theories-FixedSizeBitVectors.shtml), among
 /// other places.
-#[derive(Debug, Clone, Copy)]
-pub struct BitVec<const M: usize>(&'static Term);
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct BitVec<const M: usize>(Arc<Term>);
 impl<const M: usize> From<Const<BitVec<M>>> for BitVec<M> {
     fn from(c: Const<BitVec<M>>) -> Self {
         c.1
     }
 }
+impl<const M: usize> From<&Const<BitVec<M>>> for BitVec<M> {
+    fn from(c: &Const<BitVec<M>>) -> Self {
+        c.1.clone()
+    }
+}
 impl<const M: usize> std::fmt::Display for BitVec<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        self.0.fmt(f)
     }
 }
 
 impl<const M: usize> From<BitVec<M>> for Dynamic {
     fn from(i: BitVec<M>) -> Self {
-        Term::from(i).into()
+        Dynamic::with_sort(i, BitVec::<M>::sort())
     }
 }
 
 impl<const M: usize> From<BitVec<M>> for Term {
     fn from(i: BitVec<M>) -> Self {
-        i.0.clone()
+        Arc::unwrap_or_clone(i.0)
     }
 }
 impl<const M: usize> From<Term> for BitVec<M> {
     fn from(t: Term) -> Self {
-        BitVec(Box::leak(Box::new(t)))
+        BitVec(Arc::new(t))
     }
 }
 
@@ -52,24 +67,22 @@ fn i64_to_bit_array<const M: usize>(i: i64) -> [bool; M] {
 // }
 
 impl<const M: usize> TryFrom<BitVec<M>> for i64 {
-    type Error = std::num::ParseIntError;
+    type Error = Error;
 
     fn try_from(value: BitVec<M>) -> Result<Self, Self::Error> {
-        match value.0 {
-            Term::SpecConstant(c) => match c {
-                ast::SpecConstant::Numeral(_) => todo!(),
-                ast::SpecConstant::Decimal(_) => todo!(),
-                ast::SpecConstant::Hexadecimal(h) => h.parse(),
-                ast::SpecConstant::Binary(b) => b.parse(),
-                ast::SpecConstant::String(_) => todo!(),
-                ast::SpecConstant::Fieldelement(_) => todo!(),
-            },
-            _ => todo!(),
-        }
+        let parsed = match &*value.0 {
+            Term::SpecConstant(ast::SpecConstant::Hexadecimal(h)) => h.parse().ok(),
+            Term::SpecConstant(ast::SpecConstant::Binary(b)) => b.parse().ok(),
+            _ => None,
+        };
+        parsed.ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a bit-vector literal",
+            term: value.to_string(),
+        })
     }
 }
 impl<const M: usize> TryFrom<BitVec<M>> for [bool; M] {
-    type Error = std::num::ParseIntError;
+    type Error = Error;
 
     fn try_from(value: BitVec<M>) -> Result<Self, Self::Error> {
         Ok(i64_to_bit_array(value.try_into()?))
@@ -79,10 +92,7 @@ impl<const M: usize> TryFrom<BitVec<M>> for [bool; M] {
 impl<const M: usize> Sort for BitVec<M> {
     type Inner = Self;
     fn sort() -> ast::Sort {
-        ast::Sort::Sort(Identifier::Indexed(
-            Symbol("BitVec".to_string()),
-            vec![Index::Numeral(Numeral(M.to_string()))],
-        ))
+        bit_vec_sort(M)
     }
 }
 impl<const M: usize> From<[bool; M]> for BitVec<M> {
@@ -94,17 +104,75 @@ impl<const M: usize> From<[bool; M]> for BitVec<M> {
         .into()
     }
 }
+/// Construct the bit-vec literal with the given unsigned value. The literal is
+/// written in hexadecimal if `M` is a multiple of four, and in binary
+/// otherwise.
+///
+/// # Panics
+///
+/// Panics if `value` does not fit in `M` bits.
+impl<const M: usize> From<u64> for BitVec<M> {
+    // NOTE: `usize::is_multiple_of` requires Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    fn from(value: u64) -> Self {
+        assert!(
+            M >= 64 || value < 1 << M,
+            "{value} does not fit in a bit-vec of width {M}"
+        );
+        let literal = if M % 4 == 0 {
+            ast::SpecConstant::Hexadecimal(Hexadecimal(format!("#x{value:0w$x}", w = M / 4)))
+        } else {
+            ast::SpecConstant::Binary(Binary(format!("#b{value:0M$b}")))
+        };
+        Term::SpecConstant(literal).into()
+    }
+}
+/// Construct the bit-vec literal of the lowest `M` bits of the two's
+/// complement representation of `i`, such that `-1` is all ones. Unlike the
+/// conversion from [`u64`], values which do not fit are truncated.
 impl<const M: usize> From<i64> for BitVec<M> {
     fn from(i: i64) -> Self {
-        i64_to_bit_array(i).into()
+        if M <= 64 {
+            let mask = u64::MAX.checked_shr((64 - M) as u32).unwrap_or(0);
+            Self::from(i as u64 & mask)
+        } else {
+            // NOTE: Wider bit-vecs are sign extended
+            std::array::from_fn(|idx| (i >> (M - idx - 1).min(63)) & 1 == 1).into()
+        }
+    }
+}
+impl<const M: usize> TryFrom<BitVec<M>> for u64 {
+    type Error = Error;
+
+    fn try_from(value: BitVec<M>) -> Result<Self, Self::Error> {
+        value.to_u64()
     }
 }
 impl<const M: usize> BitVec<M> {
-    fn binop<T: From<Term>>(self, op: &str, other: BitVec<M>) -> T {
-        fun(op, vec![self.into(), other.into()]).into()
+    fn binop<T: From<Term>>(&self, op: &str, other: BitVec<M>) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
     }
-    fn unop<T: From<Term>>(self, op: &str) -> T {
-        fun(op, vec![self.into()]).into()
+    fn unop<T: From<Term>>(&self, op: &str) -> T {
+        fun(op, vec![self.clone().into()]).into()
+    }
+    /// The unsigned value of a bit-vec literal, such as those found in models.
+    ///
+    /// Returns an error if `self` is not a literal, or if its value does not
+    /// fit in a [`u64`].
+    pub fn to_u64(&self) -> Result<u64, Error> {
+        let value = match &*self.0 {
+            Term::SpecConstant(ast::SpecConstant::Hexadecimal(h)) => {
+                u64::from_str_radix(&h.0[2..], 16).ok()
+            }
+            Term::SpecConstant(ast::SpecConstant::Binary(b)) => {
+                u64::from_str_radix(&b.0[2..], 2).ok()
+            }
+            _ => None,
+        };
+        value.ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a bit-vec literal fitting in a u64",
+            term: self.to_string(),
+        })
     }
 
     #[cfg(feature = "const-bit-vec")]
@@ -115,42 +183,240 @@ impl<const M: usize> BitVec<M> {
     /// ```ignore
     /// M > I >= J
     /// ```
-    pub fn extract<const I: usize, const J: usize>(self) -> BitVec<{ I - J + 1 }> {
+    pub fn extract<const I: usize, const J: usize>(&self) -> BitVec<{ I - J + 1 }> {
         assert!(M > I);
         assert!(I >= J);
 
-        Term::Application(
-            ast::QualIdentifier::Identifier(ast::Identifier::Indexed(
-                Symbol("extract".to_string()),
-                vec![
-                    Index::Numeral(Numeral(I.to_string())),
-                    Index::Numeral(Numeral(J.to_string())),
-                ],
-            )),
-            vec![self.into()],
-        )
-        .into()
+        extract_term(I, J, self.clone().into()).into()
     }
     #[cfg(feature = "const-bit-vec")]
     /// Concatenates `self` and `other` bit-vecs to a single contiguous bit-vec
     /// with length `N + M`
-    pub fn concat<const N: usize>(self, other: impl Into<BitVec<N>>) -> BitVec<{ N + M }> {
+    pub fn concat<const N: usize>(&self, other: impl Into<BitVec<N>>) -> BitVec<{ N + M }> {
         Term::Application(
             qual_ident("concat".to_string(), None),
-            vec![self.into(), other.into().into()],
+            vec![self.clone().into(), other.into().into()],
         )
         .into()
     }
 
     // Unary
     /// Calls `(bvnot self)`
-    pub fn bvnot(self) -> Self {
+    pub fn bvnot(&self) -> Self {
         self.unop("bvnot")
     }
     /// Calls `(bvneg self)`
-    pub fn bvneg(self) -> Self {
+    pub fn bvneg(&self) -> Self {
         self.unop("bvneg")
     }
+    /// Calls `(bv2nat self)`, that is, the unsigned value of `self` as an
+    /// [`Int`].
+    ///
+    /// See [`Int::to_bv`] for the converse.
+    pub fn to_int(&self) -> Int {
+        self.unop("bv2nat")
+    }
+
+    // Binary
+    /// Calls `(bvashr self other)`, that is, an arithmetic shift right which
+    /// fills the vacated bits with the sign bit.
+    pub fn bvashr(&self, other: impl Into<Self>) -> Self {
+        self.binop("bvashr", other.into())
+    }
+    /// Calls `(bvsdiv self other)`, that is, two's complement signed division
+    pub fn bvsdiv(&self, other: impl Into<Self>) -> Self {
+        self.binop("bvsdiv", other.into())
+    }
+    /// Calls `(bvsrem self other)`, that is, two's complement signed remainder
+    /// where the sign follows `self`
+    pub fn bvsrem(&self, other: impl Into<Self>) -> Self {
+        self.binop("bvsrem", other.into())
+    }
+    /// Calls `(bvsmod self other)`, that is, two's complement signed remainder
+    /// where the sign follows `other`
+    pub fn bvsmod(&self, other: impl Into<Self>) -> Self {
+        self.binop("bvsmod", other.into())
+    }
+
+    // Comparisons
+    /// Calls `(bvult self other)`, that is, unsigned less than
+    pub fn bvult(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvult", other.into())
+    }
+    /// Calls `(bvule self other)`, that is, unsigned less than or equal
+    pub fn bvule(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvule", other.into())
+    }
+    /// Calls `(bvugt self other)`, that is, unsigned greater than
+    pub fn bvugt(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvugt", other.into())
+    }
+    /// Calls `(bvuge self other)`, that is, unsigned greater than or equal
+    pub fn bvuge(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvuge", other.into())
+    }
+    /// Calls `(bvslt self other)`, that is, signed less than
+    pub fn bvslt(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvslt", other.into())
+    }
+    /// Calls `(bvsle self other)`, that is, signed less than or equal
+    pub fn bvsle(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvsle", other.into())
+    }
+    /// Calls `(bvsgt self other)`, that is, signed greater than
+    pub fn bvsgt(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvsgt", other.into())
+    }
+    /// Calls `(bvsge self other)`, that is, signed greater than or equal
+    pub fn bvsge(&self, other: impl Into<Self>) -> Bool {
+        self.binop("bvsge", other.into())
+    }
+}
+
+#[cfg(feature = "const-bit-vec")]
+/// Concatenates `a` and `b` to a single contiguous bit-vec with length `N + M`,
+/// where the bits of `a` are the most significant.
+///
+/// This is the free function equivalent of [`BitVec::concat`].
+pub fn concat<const N: usize, const M: usize>(a: BitVec<N>, b: BitVec<M>) -> BitVec<{ N + M }> {
+    fun("concat", vec![a.into(), b.into()]).into()
+}
+
+fn extract_term(i: usize, j: usize, t: Term) -> Term {
+    Term::Application(
+        ast::QualIdentifier::Identifier(ast::Identifier::Indexed(
+            Symbol("extract".to_string()),
+            vec![
+                Index::Numeral(Numeral(i.to_string())),
+                Index::Numeral(Numeral(j.to_string())),
+            ],
+        )),
+        vec![t],
+    )
+}
+fn bit_vec_sort(width: usize) -> ast::Sort {
+    ast::Sort::Sort(Identifier::Indexed(
+        Symbol("BitVec".to_string()),
+        vec![Index::Numeral(Numeral(width.to_string()))],
+    ))
+}
+
+/// A bit-vec whose width is only known at runtime.
+///
+/// Unlike [`BitVec`], slicing and concatenating these does not require the
+/// `const-bit-vec` feature, at the cost of checking the widths at runtime
+/// rather than at compile time. Operations on bit-vecs with mismatching widths
+/// panic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVecDyn(Arc<Term>, usize);
+
+impl BitVecDyn {
+    fn new(t: Term, width: usize) -> Self {
+        BitVecDyn(Arc::new(t), width)
+    }
+    /// The sort of bit-vecs of the given `width`, that is, `(_ BitVec width)`
+    pub fn sort(width: usize) -> ast::Sort {
+        bit_vec_sort(width)
+    }
+    /// Construct a constant bit-vec of the given `width`. See the
+    /// documentation of [`Const`] for more information about constants.
+    pub fn from_name(name: impl Into<String>, width: usize) -> Const<Self> {
+        let name = const_symbol(name);
+        Const(
+            name.as_str().into(),
+            Self::new(
+                Term::Identifier(qual_ident(name, Some(bit_vec_sort(width)))),
+                width,
+            ),
+        )
+    }
+    /// Construct the bit-vec literal with the given bits, where the first bit
+    /// is the most significant.
+    pub fn from_bits(bits: &[bool]) -> Self {
+        Self::new(
+            Term::Identifier(qual_ident(
+                format!("#b{}", bits.iter().map(|x| *x as u8).format("")),
+                None,
+            )),
+            bits.len(),
+        )
+    }
+    /// The number of bits in the bit-vec
+    pub fn width(&self) -> usize {
+        self.1
+    }
+    /// Extract the bits `i` down to `j` (both inclusive) of the bit-vec,
+    /// producing a bit-vec of width `i - j + 1`.
+    ///
+    /// The constraints `i`, `j`, and the width `M` are:
+    ///
+    /// ```ignore
+    /// M > i >= j
+    /// ```
+    pub fn extract(&self, i: usize, j: usize) -> Self {
+        assert!(self.1 > i);
+        assert!(i >= j);
+
+        Self::new(extract_term(i, j, self.clone().into()), i - j + 1)
+    }
+    /// Concatenates `self` and `other` bit-vecs to a single contiguous bit-vec
+    /// whose width is the sum of the two.
+    pub fn concat(&self, other: impl Into<Self>) -> Self {
+        let other = other.into();
+        let width = self.1 + other.1;
+        Self::new(
+            fun("concat", vec![self.clone().into(), other.into()]),
+            width,
+        )
+    }
+    /// Construct the term representing `(= self other)`
+    pub fn _eq(&self, other: impl Into<Self>) -> Bool {
+        let other = other.into();
+        assert_eq!(self.1, other.1, "bit-vec widths differ");
+        fun("=", vec![self.clone().into(), other.into()]).into()
+    }
+    /// Construct the term representing `(distinct self other)`
+    pub fn _neq(&self, other: impl Into<Self>) -> Bool {
+        let other = other.into();
+        assert_eq!(self.1, other.1, "bit-vec widths differ");
+        fun("distinct", vec![self.clone().into(), other.into()]).into()
+    }
+}
+impl From<Const<BitVecDyn>> for BitVecDyn {
+    fn from(c: Const<BitVecDyn>) -> Self {
+        c.1
+    }
+}
+impl From<&Const<BitVecDyn>> for BitVecDyn {
+    fn from(c: &Const<BitVecDyn>) -> Self {
+        c.1.clone()
+    }
+}
+impl<const M: usize> From<BitVec<M>> for BitVecDyn {
+    fn from(b: BitVec<M>) -> Self {
+        BitVecDyn(b.0, M)
+    }
+}
+impl<const M: usize> From<Const<BitVec<M>>> for BitVecDyn {
+    fn from(c: Const<BitVec<M>>) -> Self {
+        c.1.into()
+    }
+}
+impl std::fmt::Display for BitVecDyn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl From<BitVecDyn> for Dynamic {
+    fn from(b: BitVecDyn) -> Self {
+        let sort = bit_vec_sort(b.1);
+        Dynamic::with_sort(b, sort)
+    }
+}
+impl From<BitVecDyn> for Term {
+    fn from(b: BitVecDyn) -> Self {
+        Arc::unwrap_or_clone(b.0)
+    }
 }
 
 impl<const M: usize> std::ops::Not for BitVec<M> {
@@ -177,6 +443,15 @@ macro_rules! impl_op {
                 self.1.binop(stringify!($op), rhs.into())
             }
         }
+        impl<const M: usize, R> std::ops::$trait<R> for &Const<$ty>
+        where
+            R: Into<$ty>,
+        {
+            type Output = $ty;
+            fn $fn(self, rhs: R) -> Self::Output {
+                self.1.binop(stringify!($op), rhs.into())
+            }
+        }
         impl<const M: usize, R> std::ops::$trait<R> for $ty
         where
             R: Into<$ty>,
@@ -203,12 +478,12 @@ macro_rules! impl_op {
             R: Into<$ty>,
         {
             fn $a_fn(&mut self, rhs: R) {
-                *self = *self $a_op rhs;
+                *self = self.clone() $a_op rhs;
             }
         }
         impl<const M: usize> $ty {
             #[doc = concat!("Calls `(", stringify!($op), " self other)`")]
-            pub fn $op(self, other: impl Into<Self>) -> Self {
+            pub fn $op(&self, other: impl Into<Self>) -> Self {
                 self.binop(stringify!($op), other.into())
             }
         }
@@ -217,21 +492,22 @@ macro_rules! impl_op {
 
 impl_op!(BitVec<M>, [bool; M], BitAnd, bitand, bvand, BitAndAssign, bitand_assign, &);
 impl_op!(BitVec<M>, [bool; M], BitOr, bitor, bvor, BitOrAssign, bitor_assign, |);
+impl_op!(BitVec<M>, [bool; M], BitXor, bitxor, bvxor, BitXorAssign, bitxor_assign, ^);
 impl_op!(BitVec<M>, [bool; M], Add, add, bvadd, AddAssign, add_assign, +);
-// impl_op!(BitVec<M>, [bool; M], Sub, sub, bvsub, SubAssign, sub_assign, -);
+impl_op!(BitVec<M>, [bool; M], Sub, sub, bvsub, SubAssign, sub_assign, -);
 impl_op!(BitVec<M>, [bool; M], Mul, mul, bvmul, MulAssign, mul_assign, *);
 impl_op!(BitVec<M>, [bool; M], Div, div, bvudiv, DivAssign, div_assign, /);
 impl_op!(BitVec<M>, [bool; M], Rem, rem, bvurem, RemAssign, rem_assign, %);
-impl_op!(BitVec<M>, [bool; M], Shr, shr, bvshr, ShrAssign, shr_assign, >>);
-impl_op!(BitVec<M>, [bool; M], Shl, shl, bvlshr, ShlAssign, shl_assign, <<);
+impl_op!(BitVec<M>, [bool; M], Shr, shr, bvlshr, ShrAssign, shr_assign, >>);
+impl_op!(BitVec<M>, [bool; M], Shl, shl, bvshl, ShlAssign, shl_assign, <<);
 
 #[cfg(test)]
 mod tests {
-    use smtlib_lowlevel::backend::Z3Binary;
+    use smtlib_lowlevel::{ast::Term, backend::Z3Binary};
 
-    use crate::{terms::Sort, Solver};
+    use crate::{terms::Sort, Error, Int, SatResult, Solver};
 
-    use super::BitVec;
+    use super::{concat, BitVec, BitVecDyn};
 
     #[test]
     fn bit_vec_extract_concat() -> Result<(), Box<dyn std::error::Error>> {
@@ -244,7 +520,7 @@ mod tests {
 
         solver.assert(a._eq(!d))?;
         solver.assert(b._eq(a.extract::<5, 2>()))?;
-        solver.assert(c._eq(a.concat(b)))?;
+        solver.assert(c._eq(a.concat(&b)))?;
 
         let model = solver.check_sat_with_model()?.expect_sat()?;
 
@@ -258,6 +534,135 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn bit_vec_from_model() -> Result<(), Box<dyn std::error::Error>> {
+        let a = BitVec::<8>::from_name("a");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert(a._eq(BitVec::from(0b1010_0101i64)))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(a).unwrap())?, 0b1010_0101);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_vec_from_u64() -> Result<(), Box<dyn std::error::Error>> {
+        let x = BitVec::<8>::from_name("x");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert((&x + 1u64)._eq(0u64))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(x).unwrap().to_u64()?, 255);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_vec_bitwise_ops() -> Result<(), Box<dyn std::error::Error>> {
+        let x = BitVec::<8>::from_name("x");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert((&x & 0x0fu64)._eq(0x0au64))?;
+        solver.assert(x.bvugt(0x80u64))?;
+        solver.assert(x.bvslt(0u64))?;
+        solver.assert(((&x ^ 0xffu64) >> 4u64)._eq(x.bvnot().bvlshr(4u64)))?;
+        solver.assert((&x << 1u64)._neq(x.bvashr(1u64)))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        let x = model.eval(x).unwrap().to_u64()?;
+        assert_eq!(x & 0x0f, 0x0a);
+        assert!(x > 0x80);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_vec_literal_width() {
+        assert_eq!(BitVec::<8>::from(255u64).to_string(), "#xff");
+        assert_eq!(BitVec::<6>::from(5u64).to_string(), "#b000101");
+        assert_eq!(BitVec::<64>::from(u64::MAX).to_u64().unwrap(), u64::MAX);
+        assert!(std::panic::catch_unwind(|| BitVec::<4>::from(16u64)).is_err());
+        assert_eq!(BitVec::<8>::from(-1i64).to_string(), "#xff");
+        assert_eq!(BitVec::<6>::from(-2i64).to_string(), "#b111110");
+        assert_eq!(BitVec::<4>::from(17i64).to_u64().unwrap(), 1);
+    }
+
+    #[test]
+    fn bit_vec_non_literal() -> Result<(), Box<dyn std::error::Error>> {
+        let indexed = BitVec::<4>::from(Term::parse("(_ bv5 4)")?);
+        assert!(matches!(
+            i64::try_from(indexed),
+            Err(Error::UnexpectedTerm { .. })
+        ));
+        let sum = BitVec::<4>::from_name("x") + BitVec::from(1u64);
+        assert!(matches!(
+            <[bool; 4]>::try_from(sum),
+            Err(Error::UnexpectedTerm { .. })
+        ));
+        assert_eq!(i64::try_from(BitVec::<4>::from(5u64))?, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_vec_extract_low_bits() -> Result<(), Box<dyn std::error::Error>> {
+        let a = BitVec::<8>::from_name("a");
+        let b = BitVec::<4>::from_name("b");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert(a._eq([true, false, true, true, false, true, false, true]))?;
+        solver.assert(b._eq(a.extract::<3, 0>()))?;
+        solver.assert(
+            concat(BitVec::from(&b), a.extract::<7, 4>())
+                ._neq([false, true, false, true, true, false, true, true]),
+        )?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bit_vec_dyn_extract_concat() -> Result<(), Box<dyn std::error::Error>> {
+        let a = BitVecDyn::from_name("a", 8);
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert(a._eq(BitVecDyn::from_bits(&[
+            true, false, true, true, false, true, false, true,
+        ])))?;
+        let low = a.extract(3, 0);
+        assert_eq!(low.width(), 4);
+        assert_eq!(low.concat(a).width(), 12);
+        solver.assert(low._neq(BitVecDyn::from_bits(&[false, true, false, true])))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_conversions() -> Result<(), Box<dyn std::error::Error>> {
+        let x = BitVec::<8>::from_name("x");
+        let i = Int::from(261).to_bv::<8>();
+        assert_eq!(i.to_string(), "((_ int2bv 8) 261)");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert(x.to_int()._eq(Int::from(5)))?;
+        solver.assert(x._eq(i))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(x).unwrap().to_u64()?, 0x05);
+
+        Ok(())
+    }
+
     // #[test]
     // fn bit_vec_math() -> Result<(), Box<dyn std::error::Error>> {
     //     let a = BitVec::<6>::from_name("a");
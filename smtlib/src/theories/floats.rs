@@ -0,0 +1,250 @@
+#![doc = concat!("```ignore\n", include_str!("./FloatingPoint.smt2"), "```")]
+
+use std::sync::Arc;
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Index, QualIdentifier, Term},
+    lexicon::{Numeral, Symbol},
+};
+
+use crate::{
+    terms::{fun, qual_ident, Const, Dynamic, Sort},
+    Bool,
+};
+
+/// The rounding modes used by the floating point operations whose result
+/// might not be representable exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// `RNE`: Round to the nearest value, and to the even value on ties.
+    NearestTiesToEven,
+    /// `RNA`: Round to the nearest value, and away from zero on ties.
+    NearestTiesToAway,
+    /// `RTP`: Round towards positive infinity.
+    TowardPositive,
+    /// `RTN`: Round towards negative infinity.
+    TowardNegative,
+    /// `RTZ`: Round towards zero.
+    TowardZero,
+}
+
+impl std::fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundingMode::NearestTiesToEven => write!(f, "RNE"),
+            RoundingMode::NearestTiesToAway => write!(f, "RNA"),
+            RoundingMode::TowardPositive => write!(f, "RTP"),
+            RoundingMode::TowardNegative => write!(f, "RTN"),
+            RoundingMode::TowardZero => write!(f, "RTZ"),
+        }
+    }
+}
+impl From<RoundingMode> for Term {
+    fn from(rm: RoundingMode) -> Self {
+        Term::Identifier(qual_ident(rm.to_string(), None))
+    }
+}
+
+/// A [`Float`] is a term containing an [IEEE 754 floating point
+/// number](https://en.wikipedia.org/wiki/IEEE_754) with `E` exponent bits and
+/// `S` significand bits (including the hidden bit). You can [read more
+/// here.](https://smtlib.cs.uiowa.edu/theories-FloatingPoint.shtml).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Float<const E: usize, const S: usize>(Arc<Term>);
+
+/// A single precision floating point number, matching [`f32`].
+pub type Float32 = Float<8, 24>;
+/// A double precision floating point number, matching [`f64`].
+pub type Float64 = Float<11, 53>;
+
+impl<const E: usize, const S: usize> From<Const<Float<E, S>>> for Float<E, S> {
+    fn from(c: Const<Float<E, S>>) -> Self {
+        c.1
+    }
+}
+impl<const E: usize, const S: usize> From<&Const<Float<E, S>>> for Float<E, S> {
+    fn from(c: &Const<Float<E, S>>) -> Self {
+        c.1.clone()
+    }
+}
+impl<const E: usize, const S: usize> std::fmt::Display for Float<E, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const E: usize, const S: usize> From<Float<E, S>> for Dynamic {
+    fn from(i: Float<E, S>) -> Self {
+        Dynamic::with_sort(i, Float::<E, S>::sort())
+    }
+}
+
+impl<const E: usize, const S: usize> From<Float<E, S>> for Term {
+    fn from(i: Float<E, S>) -> Self {
+        Arc::unwrap_or_clone(i.0)
+    }
+}
+impl<const E: usize, const S: usize> From<Term> for Float<E, S> {
+    fn from(t: Term) -> Self {
+        Float(Arc::new(t))
+    }
+}
+impl<const E: usize, const S: usize> Sort for Float<E, S> {
+    type Inner = Self;
+    fn sort() -> ast::Sort {
+        ast::Sort::Sort(Identifier::Indexed(
+            Symbol("FloatingPoint".to_string()),
+            vec![
+                Index::Numeral(Numeral(E.to_string())),
+                Index::Numeral(Numeral(S.to_string())),
+            ],
+        ))
+    }
+}
+impl From<f32> for Float32 {
+    fn from(x: f32) -> Self {
+        let bits = x.to_bits() as u64;
+        Float::from_bits(bits >> 31 == 1, (bits >> 23) & 0xff, bits & 0x7f_ffff)
+    }
+}
+impl From<f64> for Float64 {
+    fn from(x: f64) -> Self {
+        let bits = x.to_bits();
+        Float::from_bits(
+            bits >> 63 == 1,
+            (bits >> 52) & 0x7ff,
+            bits & 0xf_ffff_ffff_ffff,
+        )
+    }
+}
+
+fn binary_literal(value: u64, width: usize) -> Term {
+    assert!(
+        width >= 64 || value >> width == 0,
+        "{value} does not fit in {width} bits"
+    );
+    Term::Identifier(qual_ident(format!("#b{value:0width$b}"), None))
+}
+
+impl<const E: usize, const S: usize> Float<E, S> {
+    fn indexed(name: &str) -> Self {
+        Term::Identifier(QualIdentifier::Identifier(Identifier::Indexed(
+            Symbol(name.to_string()),
+            vec![
+                Index::Numeral(Numeral(E.to_string())),
+                Index::Numeral(Numeral(S.to_string())),
+            ],
+        )))
+        .into()
+    }
+    fn binop<T: From<Term>>(&self, op: &str, other: Float<E, S>) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
+    }
+    fn rounded_binop(&self, op: &str, rm: RoundingMode, other: Float<E, S>) -> Self {
+        fun(op, vec![rm.into(), self.clone().into(), other.into()]).into()
+    }
+
+    /// Construct the term expressing `(_ +oo E S)`, that is, positive
+    /// infinity.
+    pub fn infinity() -> Self {
+        Self::indexed("+oo")
+    }
+    /// Construct the term expressing `(_ -oo E S)`, that is, negative
+    /// infinity.
+    pub fn neg_infinity() -> Self {
+        Self::indexed("-oo")
+    }
+    /// Construct the term expressing `(_ NaN E S)`, that is, not-a-number.
+    pub fn nan() -> Self {
+        Self::indexed("NaN")
+    }
+    /// Construct the term expressing `(fp sign exponent significand)`, that
+    /// is, the float with the given bit representation. The `exponent` must
+    /// fit in `E` bits, and the `significand` must fit in `S - 1` bits, since
+    /// the hidden bit is not stored.
+    pub fn from_bits(sign: bool, exponent: u64, significand: u64) -> Self {
+        fun(
+            "fp",
+            vec![
+                binary_literal(sign as u64, 1),
+                binary_literal(exponent, E),
+                binary_literal(significand, S - 1),
+            ],
+        )
+        .into()
+    }
+
+    /// Construct the term expressing `(fp.add rm self other)`
+    pub fn add(&self, rm: RoundingMode, other: impl Into<Self>) -> Self {
+        self.rounded_binop("fp.add", rm, other.into())
+    }
+    /// Construct the term expressing `(fp.sub rm self other)`
+    pub fn sub(&self, rm: RoundingMode, other: impl Into<Self>) -> Self {
+        self.rounded_binop("fp.sub", rm, other.into())
+    }
+    /// Construct the term expressing `(fp.mul rm self other)`
+    pub fn mul(&self, rm: RoundingMode, other: impl Into<Self>) -> Self {
+        self.rounded_binop("fp.mul", rm, other.into())
+    }
+    /// Construct the term expressing `(fp.div rm self other)`
+    pub fn div(&self, rm: RoundingMode, other: impl Into<Self>) -> Self {
+        self.rounded_binop("fp.div", rm, other.into())
+    }
+
+    /// Construct the term expressing `(fp.leq self other)`
+    pub fn leq(&self, other: impl Into<Self>) -> Bool {
+        self.binop("fp.leq", other.into())
+    }
+    /// Construct the term expressing `(fp.lt self other)`
+    pub fn lt(&self, other: impl Into<Self>) -> Bool {
+        self.binop("fp.lt", other.into())
+    }
+    /// Construct the term expressing `(fp.geq self other)`
+    pub fn geq(&self, other: impl Into<Self>) -> Bool {
+        self.binop("fp.geq", other.into())
+    }
+    /// Construct the term expressing `(fp.gt self other)`
+    pub fn gt(&self, other: impl Into<Self>) -> Bool {
+        self.binop("fp.gt", other.into())
+    }
+}
+
+impl<const E: usize, const S: usize> std::ops::Neg for Float<E, S> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        fun("fp.neg", vec![self.into()]).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{terms::Sort, SatResult, Solver};
+
+    use super::{Float64, RoundingMode};
+
+    #[test]
+    fn literals() {
+        insta::assert_snapshot!(Float64::from(1.0), @"(fp #b0 #b01111111111 #b0000000000000000000000000000000000000000000000000000)");
+        insta::assert_snapshot!(Float64::from(-0.5), @"(fp #b1 #b01111111110 #b0000000000000000000000000000000000000000000000000000)");
+        insta::assert_snapshot!(Float64::infinity(), @"(_ +oo 11 53)");
+    }
+
+    #[test]
+    fn addition() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Float64::from_name("x");
+        solver.assert(x._eq(Float64::from(0.5).add(RoundingMode::NearestTiesToEven, 0.5)))?;
+        solver.assert(x._neq(1.0))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+}
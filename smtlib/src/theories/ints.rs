@@ -1,46 +1,60 @@
 #![doc = concat!("```ignore\n", include_str!("./Ints.smt2"), "```")]
 
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use num_traits::Signed;
 use smtlib_lowlevel::{
-    ast::{self, Identifier, Term},
-    lexicon::Symbol,
+    ast::{self, Identifier, Index, QualIdentifier, Term},
+    lexicon::{Numeral, Symbol},
 };
 
 use crate::{
-    impl_op,
-    terms::{fun, qual_ident, Const, Dynamic, Sort},
-    Bool,
+    and, impl_neg, impl_op,
+    terms::{fun, ite, qual_ident, Arithmetic, Const, Dynamic, Sort},
+    BitVec, Bool, Error, Real,
 };
 
 /// A [`Int`] is a term containing a
 /// [integer](https://en.wikipedia.org/wiki/Integer). You can [read more
 /// here.](https://smtlib.cs.uiowa.edu/theories-Ints.shtml).
-#[derive(Debug, Clone, Copy)]
-pub struct Int(&'static Term);
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Int(Arc<Term>);
 impl From<Const<Int>> for Int {
     fn from(c: Const<Int>) -> Self {
         c.1
     }
 }
+impl From<&Const<Int>> for Int {
+    fn from(c: &Const<Int>) -> Self {
+        c.1.clone()
+    }
+}
 impl std::fmt::Display for Int {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        self.0.fmt(f)
     }
 }
 
 impl From<Int> for Dynamic {
     fn from(i: Int) -> Self {
-        Term::from(i).into()
+        Dynamic::with_sort(i, Int::sort())
     }
 }
 
 impl From<Int> for Term {
     fn from(i: Int) -> Self {
-        i.0.clone()
+        Arc::unwrap_or_clone(i.0)
     }
 }
 impl From<Term> for Int {
     fn from(t: Term) -> Self {
-        Int(Box::leak(Box::new(t)))
+        Int(Arc::new(t))
     }
 }
 impl Sort for Int {
@@ -49,47 +63,386 @@ impl Sort for Int {
         ast::Sort::Sort(Identifier::Simple(Symbol("Int".into())))
     }
 }
+impl Arithmetic for Int {}
 impl From<i64> for Int {
     fn from(i: i64) -> Self {
         Term::Identifier(qual_ident(i.to_string(), None)).into()
     }
 }
+fn int_value(t: &Term) -> Option<BigInt> {
+    match t {
+        Term::SpecConstant(ast::SpecConstant::Numeral(n)) => n.0.parse().ok(),
+        Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args)
+            if op.0 == "-" && args.len() == 1 =>
+        {
+            Some(-int_value(&args[0])?)
+        }
+        _ => None,
+    }
+}
+/// Converts an integer literal, such as those found in models, to its value.
+/// Negative values are of the form `(- n)`, as numerals are non-negative.
+///
+/// Unlike the conversions to fixed-width integers, this never fails for
+/// integer literals, however large.
+impl TryFrom<Int> for BigInt {
+    type Error = Error;
+
+    fn try_from(value: Int) -> Result<Self, Self::Error> {
+        int_value(&value.0).ok_or_else(|| Error::UnexpectedTerm {
+            expected: "an integer literal",
+            term: value.to_string(),
+        })
+    }
+}
+impl TryFrom<Int> for i64 {
+    type Error = Error;
+
+    fn try_from(value: Int) -> Result<Self, Self::Error> {
+        int_value(&value.0)
+            .and_then(|i| i64::try_from(i).ok())
+            .ok_or_else(|| Error::UnexpectedTerm {
+                expected: "an integer literal fitting in an i64",
+                term: value.to_string(),
+            })
+    }
+}
+impl TryFrom<Int> for i128 {
+    type Error = Error;
+
+    fn try_from(value: Int) -> Result<Self, Self::Error> {
+        int_value(&value.0)
+            .and_then(|i| i128::try_from(i).ok())
+            .ok_or_else(|| Error::UnexpectedTerm {
+                expected: "an integer literal fitting in an i128",
+                term: value.to_string(),
+            })
+    }
+}
 impl Int {
-    fn binop<T: From<Term>>(self, op: &str, other: Int) -> T {
-        fun(op, vec![self.into(), other.into()]).into()
+    /// Construct the integer literal of `n`. See [`Int::from_bigint`].
+    ///
+    /// This is not provided as `From<i128>`, as that would make integer
+    /// literals such as the `2` in `x.gt(2)` ambiguous.
+    pub fn from_i128(n: i128) -> Int {
+        Int::from_bigint(&BigInt::from(n))
+    }
+    /// Construct the integer literal of `n`, such as `5`, or its negation
+    /// such as `(- 5)` for negative values, as numerals in SMT-LIB are
+    /// non-negative.
+    pub fn from_bigint(n: &BigInt) -> Int {
+        let literal = Term::SpecConstant(ast::SpecConstant::Numeral(Numeral(
+            n.magnitude().to_string(),
+        )));
+        if n.is_negative() {
+            fun("-", vec![literal]).into()
+        } else {
+            literal.into()
+        }
+    }
+    fn binop<T: From<Term>>(&self, op: &str, other: Int) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
     }
     /// Construct the term expressing `(> self other)`
-    pub fn gt(self, other: impl Into<Self>) -> Bool {
+    pub fn gt(&self, other: impl Into<Self>) -> Bool {
         self.binop(">", other.into())
     }
     /// Construct the term expressing `(>= self other)`
-    pub fn ge(self, other: impl Into<Self>) -> Bool {
+    pub fn ge(&self, other: impl Into<Self>) -> Bool {
         self.binop(">=", other.into())
     }
     /// Construct the term expressing `(< self other)`
-    pub fn lt(self, other: impl Into<Self>) -> Bool {
+    pub fn lt(&self, other: impl Into<Self>) -> Bool {
         self.binop("<", other.into())
     }
     /// Construct the term expressing `(<= self other)`
-    pub fn le(self, other: impl Into<Self>) -> Bool {
+    pub fn le(&self, other: impl Into<Self>) -> Bool {
         self.binop("<=", other.into())
     }
-    // TODO: This seems to not be supported by z3?
+    /// Construct the term expressing `(and (>= self lo) (< self hi))`, that is,
+    /// whether `self` is in the half-open range `[lo, hi)`.
+    pub fn in_range(&self, lo: impl Into<Self>, hi: impl Into<Self>) -> Bool {
+        and([self.ge(lo), self.lt(hi)])
+    }
+    /// Construct the term expressing `(and (>= self lo) (<= self hi))`, that
+    /// is, whether `self` is in the closed range `[lo, hi]`.
+    pub fn between(&self, lo: impl Into<Self>, hi: impl Into<Self>) -> Bool {
+        and([self.ge(lo), self.le(hi)])
+    }
+    /// Construct the term expressing `(div self other)`, that is, integer
+    /// division rounding such that the remainder is always non-negative.
+    ///
+    /// Division by zero is total but unspecified in SMT-LIB, meaning that
+    /// `x div 0` is _some_ integer, which the solver is free to choose.
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(&self, other: impl Into<Self>) -> Int {
+        self.binop("div", other.into())
+    }
+    /// Construct the term expressing `(mod self other)`. The result is always
+    /// non-negative, and satisfies `self = other * (self div other) + (self
+    /// mod other)`.
+    ///
+    /// As with [`Int::div`], the result of `x mod 0` is unspecified.
+    pub fn modulo(&self, other: impl Into<Self>) -> Int {
+        self.binop("mod", other.into())
+    }
+    /// Construct the term expressing `((_ divisible n) self)`, that is,
+    /// whether `self` is a multiple of `n`.
+    ///
+    /// Panics if `n` is zero, as the SMT-LIB index must be positive.
+    pub fn divisible_by(&self, n: u64) -> Bool {
+        assert!(n > 0, "divisibility is only defined for positive divisors");
+        Term::Application(
+            QualIdentifier::Identifier(Identifier::Indexed(
+                Symbol("divisible".to_string()),
+                vec![Index::Numeral(Numeral(n.to_string()))],
+            )),
+            vec![self.clone().into()],
+        )
+        .into()
+    }
     /// Construct the term expressing `(abs self)`
-    pub fn abs(self) -> Int {
-        fun("abs", vec![self.into()]).into()
+    pub fn abs(&self) -> Int {
+        fun("abs", vec![self.clone().into()]).into()
     }
-}
-
-impl std::ops::Neg for Int {
-    type Output = Self;
-    fn neg(self) -> Self::Output {
-        fun("-", vec![self.into()]).into()
+    /// Construct the term expressing `(ite (>= self other) self other)`, that
+    /// is, the larger of `self` and `other`.
+    pub fn max(&self, other: impl Into<Self>) -> Int {
+        let other = other.into();
+        ite(self.ge(other.clone()), self.clone(), other)
+    }
+    /// Construct the term expressing `(ite (<= self other) self other)`, that
+    /// is, the smaller of `self` and `other`.
+    pub fn min(&self, other: impl Into<Self>) -> Int {
+        let other = other.into();
+        ite(self.le(other.clone()), self.clone(), other)
+    }
+    /// Construct the term expressing `self` raised to the power of `exp`, as
+    /// `exp` factors of `self` multiplied using `*`. For `exp = 0` this is the
+    /// literal `1`, and for `exp = 1` it is `self`, keeping the term linear.
+    ///
+    /// This avoids the non-standard `^` operator, which not all solvers
+    /// support. Note that for `exp > 1` the term is nonlinear, and thus
+    /// requires a logic such as `QF_NIA`.
+    pub fn pow(&self, exp: u32) -> Int {
+        match exp {
+            0 => Int::from(1),
+            1 => self.clone(),
+            _ => fun("*", (0..exp).map(|_| self.clone().into()).collect()).into(),
+        }
+    }
+    /// Construct the term expressing `(to_real self)`, that is, `self` as a
+    /// [`Real`].
+    pub fn to_real(&self) -> Real {
+        fun("to_real", vec![self.clone().into()]).into()
+    }
+    /// Construct the term expressing `((_ int2bv N) self)`, that is, the
+    /// `N`-bit bit-vec of `self` modulo `2^N`.
+    ///
+    /// See [`BitVec::to_int`] for the converse.
+    pub fn to_bv<const N: usize>(&self) -> BitVec<N> {
+        Term::Application(
+            QualIdentifier::Identifier(Identifier::Indexed(
+                Symbol("int2bv".to_string()),
+                vec![Index::Numeral(Numeral(N.to_string()))],
+            )),
+            vec![self.clone().into()],
+        )
+        .into()
     }
 }
 
+impl_neg!(Int);
+
 impl_op!(Int, i64, Add, add, "+", AddAssign, add_assign, +);
 impl_op!(Int, i64, Sub, sub, "-", SubAssign, sub_assign, -);
 impl_op!(Int, i64, Mul, mul, "*", MulAssign, mul_assign, *);
 impl_op!(Int, i64, Div, div, "div", DivAssign, div_assign, /);
 impl_op!(Int, i64, Rem, rem, "mod", RemAssign, rem_assign, %);
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use num_bigint::BigInt;
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{
+        terms::{self, Sort},
+        Logic, SatResult, Solver,
+    };
+
+    use super::Int;
+
+    #[test]
+    fn div_mod_abs() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        let y = Int::from_name("y");
+        solver.assert(x.modulo(3)._eq(1))?;
+        solver.assert(x.div(3)._eq(2))?;
+        solver.assert(y._eq((-Int::from(&x)).abs()))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 7);
+        assert_eq!(i64::try_from(model.eval(y).unwrap())?, 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pow() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.set_logic(Logic::QF_NIA)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.pow(3)._eq(27))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x.clone()).unwrap())?, 3);
+        assert_eq!(x.pow(0).to_string(), "1");
+        assert_eq!(x.pow(1).to_string(), x.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_range() -> Result<(), Box<dyn std::error::Error>> {
+        let x = Int::from_name("x");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(x.in_range(0, 8))?;
+        solver.assert(x._eq(7))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(x.in_range(0, 8))?;
+        solver.assert(x._eq(8))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(x.between(0, 8))?;
+        solver.assert(x._eq(8))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn sum() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let xs: Vec<_> = (0..10).map(|i| Int::from_name(format!("x{i}"))).collect();
+        for (i, x) in xs.iter().enumerate() {
+            solver.assert(x._eq(i as i64))?;
+        }
+        let total = terms::sum(xs.iter().map(Int::from));
+        assert!(total.to_string().starts_with("(+ (as x0 Int) (as x1 Int)"));
+        solver.assert(total._neq(45))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        assert_eq!(terms::sum::<Int>([]).to_string(), "0");
+        assert_eq!(terms::product::<Int>([]).to_string(), "1");
+        assert_eq!(terms::product([Int::from(3)]).to_string(), "3");
+
+        Ok(())
+    }
+
+    #[test]
+    fn divisible_by() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.divisible_by(3))?;
+        solver.assert(x.in_range(1, 6))?;
+        assert_eq!(
+            x.divisible_by(3).to_string(),
+            "((_ divisible 3) (as x Int))"
+        );
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x).unwrap())?, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn neg() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert((-&x)._eq(-Int::from(5)))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(i64::try_from(model.eval(x.clone()).unwrap())?, 5);
+        assert_eq!((-x).to_string(), "(- (as x Int))");
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_i128() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let big = i64::MAX as i128 * 4 + 3;
+        let x = Int::from_name("x");
+        solver.assert(x._eq(Int::from_i128(big)))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        let value = model.eval(x).unwrap();
+        assert_eq!(i128::try_from(value.clone())?, big);
+        assert!(i64::try_from(value).is_err());
+
+        assert_eq!(Int::from_i128(-big).to_string(), "(- 36893488147419103231)");
+        assert_eq!(i128::try_from(Int::from_i128(-big))?, -big);
+        assert_eq!(Int::from_bigint(&BigInt::from(0)).to_string(), "0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn bigint_model_value() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let big: BigInt = "-1234567890123456789012345678901234567890123456789".parse()?;
+        let x = Int::from_name("x");
+        solver.assert((&x + 1)._eq(Int::from_bigint(&big)))?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        let value = model.eval(x).unwrap();
+        assert_eq!(BigInt::try_from(value.clone())?, big - 1);
+        assert!(i128::try_from(value).is_err());
+        assert!(BigInt::try_from(Int::from(Int::from_name("y"))).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn min_max() -> Result<(), Box<dyn std::error::Error>> {
+        let a = Int::from(3);
+        assert_eq!(a.max(7).to_string(), "(ite (>= 3 7) 3 7)");
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert(a.max(7)._neq(7) | a.min(7)._neq(3))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hash_eq() {
+        let x = Int::from_name("x");
+        let terms: HashSet<Int> = [&x + 1, &x + 1, &x * 2].into_iter().collect();
+        assert_eq!(terms.len(), 2);
+        assert!(terms.contains(&(&x + 1)));
+        assert_ne!(Int::from(1), Int::from(2));
+    }
+
+    #[test]
+    fn terms_are_freed() {
+        let x = Int::from_name("x");
+        let sum = (x + 1) * 2;
+        let weak = Arc::downgrade(&sum.0);
+        drop(sum);
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -1,8 +1,15 @@
 //! Theories in SMT-LIB are definitions of [sorts](crate::Sort) and in general
 //! functions present in the [logics](crate::Logic).
 
+pub mod arrays;
 pub mod core;
+pub mod datatypes;
 pub mod fixed_size_bit_vectors;
 pub mod ints;
 pub mod reals;
 pub mod fieldelements;
+pub mod floats;
+pub mod sets;
+pub mod strings;
+pub mod tuples;
+pub mod uninterpreted;
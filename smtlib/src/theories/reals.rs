@@ -1,46 +1,58 @@
 #![doc = concat!("```ignore\n", include_str!("./Reals.smt2"), "```")]
 
+use std::sync::Arc;
+
 use smtlib_lowlevel::{
-    ast::{self, Identifier, Term},
-    lexicon::Symbol,
+    ast::{self, Identifier, QualIdentifier, Term},
+    lexicon::{Decimal, Symbol},
 };
 
 use crate::{
-    impl_op,
-    terms::{fun, qual_ident, Const, Dynamic, Sort},
-    Bool,
+    impl_neg, impl_op,
+    terms::{fun, ite, qual_ident, Arithmetic, Const, Dynamic, Sort},
+    Bool, Error, Int,
 };
 
 /// A [`Real`] is a term containing a
 /// [real](https://en.wikipedia.org/wiki/Real_number). You can [read more
 /// here.](https://smtlib.cs.uiowa.edu/theories-Reals.shtml).
-#[derive(Debug, Clone, Copy)]
-pub struct Real(&'static Term);
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Real(Arc<Term>);
 impl From<Const<Real>> for Real {
     fn from(c: Const<Real>) -> Self {
         c.1
     }
 }
+impl From<&Const<Real>> for Real {
+    fn from(c: &Const<Real>) -> Self {
+        c.1.clone()
+    }
+}
 impl std::fmt::Display for Real {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Term::from(*self).fmt(f)
+        self.0.fmt(f)
     }
 }
 
 impl From<Real> for Dynamic {
     fn from(i: Real) -> Self {
-        Term::from(i).into()
+        Dynamic::with_sort(i, Real::sort())
     }
 }
 
 impl From<Real> for Term {
     fn from(i: Real) -> Self {
-        i.0.clone()
+        Arc::unwrap_or_clone(i.0)
     }
 }
 impl From<Term> for Real {
     fn from(t: Term) -> Self {
-        Real(Box::leak(Box::new(t)))
+        Real(Arc::new(t))
     }
 }
 impl Sort for Real {
@@ -49,50 +61,254 @@ impl Sort for Real {
         ast::Sort::Sort(Identifier::Simple(Symbol("Real".into())))
     }
 }
+impl Arithmetic for Real {}
 impl From<i64> for Real {
     fn from(i: i64) -> Self {
         Term::Identifier(qual_ident(i.to_string(), None)).into()
     }
 }
+/// Constructs a decimal literal with the exact value of the [`f64`], such as
+/// `2.5`, or its negation such as `(- 2.5)` for negative values.
+///
+/// Note that most decimal fractions, such as `0.1`, cannot be represented
+/// exactly by an [`f64`], in which case [`Real::rational`] should be used
+/// instead.
+///
+/// Panics if the value is NaN or infinite, as these are not real numbers.
 impl From<f64> for Real {
-    fn from(i: f64) -> Self {
-        Term::Identifier(qual_ident(i.to_string(), None)).into()
+    fn from(x: f64) -> Self {
+        assert!(x.is_finite(), "{x} is not a real number");
+        // NOTE: `Display` for `f64` never uses exponent notation, but omits
+        // the fractional part of integral values
+        let mut digits = x.abs().to_string();
+        if !digits.contains('.') {
+            digits.push_str(".0");
+        }
+        let literal = Term::SpecConstant(ast::SpecConstant::Decimal(Decimal(digits)));
+        if x < 0.0 {
+            fun("-", vec![literal]).into()
+        } else {
+            literal.into()
+        }
+    }
+}
+fn real_value(t: &Term) -> Option<f64> {
+    match t {
+        Term::SpecConstant(ast::SpecConstant::Numeral(n)) => n.0.parse().ok(),
+        Term::SpecConstant(ast::SpecConstant::Decimal(d)) => d.0.parse().ok(),
+        Term::Application(QualIdentifier::Identifier(Identifier::Simple(op)), args) => {
+            match (op.0.as_str(), args.as_slice()) {
+                ("-", [x]) => Some(-real_value(x)?),
+                ("/", [x, y]) => Some(real_value(x)? / real_value(y)?),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+/// Converts a real value, such as those found in models, to the nearest
+/// [`f64`]. Rationals of the form `(/ n d)` are evaluated, and thus might
+/// lose precision.
+impl TryFrom<Real> for f64 {
+    type Error = Error;
+
+    fn try_from(value: Real) -> Result<Self, Self::Error> {
+        real_value(&value.0).ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a real literal",
+            term: value.to_string(),
+        })
     }
 }
 impl Real {
-    fn binop<T: From<Term>>(self, op: &str, other: Real) -> T {
-        fun(op, vec![self.into(), other.into()]).into()
+    /// Construct the term expressing `(/ num den)`, that is, the exact
+    /// rational number `num / den`.
+    ///
+    /// Panics if `den` is zero.
+    pub fn rational(num: i64, den: i64) -> Real {
+        assert!(den != 0, "the denominator of a rational must not be zero");
+        let decimal = |n: i64| {
+            let digits = format!("{}.0", n.unsigned_abs());
+            Real::from(Term::SpecConstant(ast::SpecConstant::Decimal(Decimal(
+                digits,
+            ))))
+        };
+        let abs = decimal(num).div(decimal(den));
+        if num != 0 && (num < 0) != (den < 0) {
+            -abs
+        } else {
+            abs
+        }
+    }
+    fn binop<T: From<Term>>(&self, op: &str, other: Real) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
     }
     /// Construct the term expressing `(> self other)`
-    pub fn gt(self, other: impl Into<Self>) -> Bool {
+    pub fn gt(&self, other: impl Into<Self>) -> Bool {
         self.binop(">", other.into())
     }
     /// Construct the term expressing `(>= self other)`
-    pub fn ge(self, other: impl Into<Self>) -> Bool {
+    pub fn ge(&self, other: impl Into<Self>) -> Bool {
         self.binop(">=", other.into())
     }
     /// Construct the term expressing `(< self other)`
-    pub fn lt(self, other: impl Into<Self>) -> Bool {
+    pub fn lt(&self, other: impl Into<Self>) -> Bool {
         self.binop("<", other.into())
     }
     /// Construct the term expressing `(<= self other)`
-    pub fn le(self, other: impl Into<Self>) -> Bool {
+    pub fn le(&self, other: impl Into<Self>) -> Bool {
         self.binop("<=", other.into())
     }
     /// Construct the term expressing `(abs self)`
-    pub fn abs(self) -> Real {
-        fun("abs", vec![self.into()]).into()
+    pub fn abs(&self) -> Real {
+        fun("abs", vec![self.clone().into()]).into()
     }
-}
-
-impl std::ops::Neg for Real {
-    type Output = Self;
-    fn neg(self) -> Self::Output {
-        fun("-", vec![self.into()]).into()
+    /// Construct the term expressing `(ite (>= self other) self other)`, that
+    /// is, the larger of `self` and `other`.
+    pub fn max(&self, other: impl Into<Self>) -> Real {
+        let other = other.into();
+        ite(self.ge(other.clone()), self.clone(), other)
+    }
+    /// Construct the term expressing `(ite (<= self other) self other)`, that
+    /// is, the smaller of `self` and `other`.
+    pub fn min(&self, other: impl Into<Self>) -> Real {
+        let other = other.into();
+        ite(self.le(other.clone()), self.clone(), other)
+    }
+    /// Construct the term expressing `(/ self other)`
+    pub fn div(&self, other: impl Into<Self>) -> Real {
+        self.binop("/", other.into())
+    }
+    /// Construct the term expressing `(to_int self)`, that is, the largest
+    /// integer not greater than `self`.
+    pub fn to_int(&self) -> Int {
+        fun("to_int", vec![self.clone().into()]).into()
+    }
+    /// Construct the term expressing `(is_int self)`, that is, whether `self`
+    /// is an integer.
+    pub fn is_int(&self) -> Bool {
+        fun("is_int", vec![self.clone().into()]).into()
     }
 }
 
+impl_neg!(Real);
+
 impl_op!(Real, f64, Add, add, "+", AddAssign, add_assign, +);
 impl_op!(Real, f64, Sub, sub, "-", SubAssign, sub_assign, -);
 impl_op!(Real, f64, Mul, mul, "*", MulAssign, mul_assign, *);
-impl_op!(Real, f64, Div, div, "div", DivAssign, div_assign, /);
+impl_op!(Real, f64, Div, div, "/", DivAssign, div_assign, /);
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::{
+        ast::{SpecConstant, Term},
+        backend::Z3Binary,
+        lexicon::Decimal,
+    };
+
+    use crate::{
+        terms::{fun, Sort},
+        Int, SatResult, Solver,
+    };
+
+    use super::Real;
+
+    #[test]
+    fn int_to_real() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Int::from_name("x");
+        solver.assert(x.to_real()._eq(2.5))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn real_to_int() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let r = Real::from_name("r");
+        solver.assert(r._eq(Real::from(5.0).div(2.0)))?;
+        solver.assert(r.is_int() | r.to_int()._neq(2))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn neg() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let x = Real::from_name("x");
+        solver.assert((-&x)._eq(-Real::from(5.0)))?;
+        solver.assert((&x - 1.0).gt(3.5))?;
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(f64::try_from(model.eval(x).unwrap())?, 5.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rational() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert((Real::rational(1, 3) * 3.0)._eq(1.0))?;
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.assert((Real::from(0.3333) * 3.0)._eq(1.0))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        assert_eq!(Real::rational(-1, 3).to_string(), "(- (/ 1.0 3.0))");
+        assert_eq!(Real::rational(0, -3).to_string(), "(/ 0.0 3.0)");
+        assert_eq!(f64::try_from(Real::rational(3, -4))?, -0.75);
+
+        Ok(())
+    }
+
+    #[test]
+    fn min_max() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        solver.assert(Real::from(2.5).max(-1.0)._neq(2.5))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        // NOTE: The minimum with the upper bound only equals the maximum with
+        // the lower bound when `x` is between the two
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        let x = Real::from_name("x");
+        solver.assert(x.min(2.5)._eq(x.max(-1.0)))?;
+        solver.assert(x.lt(-1.0) | x.gt(2.5))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn from_f64() {
+        assert_eq!(Real::from(5.0).to_string(), "5.0");
+        assert_eq!(Real::from(-2.5).to_string(), "(- 2.5)");
+        assert_eq!(Real::from(1e-7).to_string(), "0.0000001");
+        assert_eq!(f64::try_from(Real::from(-0.125)).unwrap(), -0.125);
+    }
+
+    #[test]
+    #[should_panic = "is not a real number"]
+    fn from_nan() {
+        let _ = Real::from(f64::NAN);
+    }
+
+    #[test]
+    fn real_from_model_term() {
+        let half = fun(
+            "/",
+            vec![
+                Term::SpecConstant(SpecConstant::Decimal(Decimal("1.0".into()))),
+                Term::SpecConstant(SpecConstant::Decimal(Decimal("2.0".into()))),
+            ],
+        );
+        let neg_half = Real::from(fun("-", vec![half]));
+        assert_eq!(f64::try_from(neg_half).unwrap(), -0.5);
+        assert!(f64::try_from(Real::from(Real::from_name("x"))).is_err());
+    }
+}
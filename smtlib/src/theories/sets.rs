@@ -0,0 +1,167 @@
+//! Finite sets, as supported by [cvc5](https://cvc5.github.io/docs/cvc5-1.0.0/theories/sets-and-relations.html).
+//!
+//! > **NOTE:** Sets are not part of the SMT-LIB standard, and are thus only
+//! > supported by some solvers.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Term},
+    lexicon::Symbol,
+};
+
+use crate::{
+    terms::{fun, qual_ident, Const, Dynamic, Sort},
+    Bool, Int,
+};
+
+/// Whether `q` is the empty set `(as set.empty (Set T))` constructed by
+/// [`Set::empty`], which is qualified by its sort like a constant, but must
+/// never be declared.
+pub(crate) fn is_empty_set(q: &ast::QualIdentifier) -> bool {
+    match q {
+        ast::QualIdentifier::Sorted(
+            Identifier::Simple(Symbol(name)),
+            ast::Sort::Parametric(Identifier::Simple(Symbol(sort)), _),
+        ) => name == "set.empty" && sort == "Set",
+        _ => false,
+    }
+}
+
+/// A [`Set`] is a term containing a finite set of elements of sort `T`.
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Set<T>(Arc<Term>, PhantomData<T>);
+
+impl<T> Clone for Set<T> {
+    fn clone(&self) -> Self {
+        Set(self.0.clone(), PhantomData)
+    }
+}
+impl<T> PartialEq for Set<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<T> Eq for Set<T> {}
+impl<T> std::hash::Hash for Set<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<T> From<Const<Set<T>>> for Set<T> {
+    fn from(c: Const<Set<T>>) -> Self {
+        c.1
+    }
+}
+impl<T> From<&Const<Set<T>>> for Set<T> {
+    fn from(c: &Const<Set<T>>) -> Self {
+        c.1.clone()
+    }
+}
+impl<T> std::fmt::Display for Set<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Sort> From<Set<T>> for Dynamic {
+    fn from(s: Set<T>) -> Self {
+        Dynamic::with_sort(s, Set::<T>::sort())
+    }
+}
+
+impl<T> From<Set<T>> for Term {
+    fn from(s: Set<T>) -> Self {
+        Arc::unwrap_or_clone(s.0)
+    }
+}
+impl<T> From<Term> for Set<T> {
+    fn from(t: Term) -> Self {
+        Set(Arc::new(t), PhantomData)
+    }
+}
+impl<T: Sort> Sort for Set<T> {
+    type Inner = Self;
+    fn sort() -> ast::Sort {
+        ast::Sort::Parametric(Identifier::Simple(Symbol("Set".into())), vec![T::sort()])
+    }
+}
+impl<T: Sort> Set<T> {
+    fn binop<R: From<Term>>(&self, op: &str, other: Set<T>) -> R {
+        fun(op, vec![self.clone().into(), other.into()]).into()
+    }
+
+    /// Construct the term expressing `(as set.empty (Set T))`, that is, the
+    /// empty set.
+    pub fn empty() -> Self {
+        Term::Identifier(qual_ident("set.empty".into(), Some(Self::sort()))).into()
+    }
+    /// Construct the term expressing `(set.singleton x)`, that is, the set
+    /// containing only `x`.
+    pub fn singleton(x: impl Into<T>) -> Self {
+        fun("set.singleton", vec![x.into().into()]).into()
+    }
+    /// Construct the term expressing `(set.member x self)`, that is, whether
+    /// `x` is an element of `self`.
+    pub fn member(&self, x: impl Into<T>) -> Bool {
+        fun("set.member", vec![x.into().into(), self.clone().into()]).into()
+    }
+    /// Construct the term expressing `(set.insert x self)`, that is, the set
+    /// containing `x` and all elements of `self`.
+    pub fn insert(&self, x: impl Into<T>) -> Self {
+        fun("set.insert", vec![x.into().into(), self.clone().into()]).into()
+    }
+    /// Construct the term expressing `(set.union self other)`
+    pub fn union(&self, other: impl Into<Self>) -> Self {
+        self.binop("set.union", other.into())
+    }
+    /// Construct the term expressing `(set.inter self other)`
+    pub fn intersection(&self, other: impl Into<Self>) -> Self {
+        self.binop("set.inter", other.into())
+    }
+    /// Construct the term expressing `(set.minus self other)`, that is, the
+    /// elements of `self` which are not in `other`.
+    pub fn setminus(&self, other: impl Into<Self>) -> Self {
+        self.binop("set.minus", other.into())
+    }
+    /// Construct the term expressing `(set.subset self other)`
+    pub fn subset(&self, other: impl Into<Self>) -> Bool {
+        self.binop("set.subset", other.into())
+    }
+    /// Construct the term expressing `(= self (as set.empty (Set T)))`
+    pub fn is_empty(&self) -> Bool {
+        self.binop("=", Self::empty())
+    }
+    /// Construct the term expressing `(set.card self)`, that is, the number of
+    /// elements in `self`.
+    pub fn card(&self) -> Int {
+        fun("set.card", vec![self.clone().into()]).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Cvc5Binary;
+
+    use crate::{terms::Sort, Int, SatResult, Solver};
+
+    use super::Set;
+
+    #[test]
+    fn member_of_insert() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Cvc5Binary::new("cvc5")?, false)?;
+
+        let s = Set::<Int>::from_name("s");
+        solver.assert(s._eq(Set::empty().insert(3)))?;
+        solver.assert(!s.member(3) | s.is_empty() | s.card()._neq(1))?;
+        assert!(matches!(solver.check_sat()?, SatResult::Unsat));
+
+        Ok(())
+    }
+}
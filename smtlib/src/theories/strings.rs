@@ -0,0 +1,189 @@
+#![doc = concat!("```ignore\n", include_str!("./UnicodeStrings.smt2"), "```")]
+
+use std::sync::Arc;
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Term},
+    lexicon::{escape_string, try_escape_string, unescape_string, Symbol},
+};
+
+use crate::{
+    terms::{fun, Const, Dynamic, Sort},
+    Bool, Error, Int,
+};
+
+/// A [`Str`] is a term containing a string of Unicode characters. You can
+/// [read more here.](https://smtlib.cs.uiowa.edu/theories-UnicodeStrings.shtml).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Term", from = "Term")
+)]
+pub struct Str(Arc<Term>);
+impl From<Const<Str>> for Str {
+    fn from(c: Const<Str>) -> Self {
+        c.1
+    }
+}
+impl From<&Const<Str>> for Str {
+    fn from(c: &Const<Str>) -> Self {
+        c.1.clone()
+    }
+}
+impl std::fmt::Display for Str {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Str> for Dynamic {
+    fn from(s: Str) -> Self {
+        Dynamic::with_sort(s, Str::sort())
+    }
+}
+
+impl From<Str> for Term {
+    fn from(s: Str) -> Self {
+        Arc::unwrap_or_clone(s.0)
+    }
+}
+impl From<Term> for Str {
+    fn from(t: Term) -> Self {
+        Str(Arc::new(t))
+    }
+}
+impl Sort for Str {
+    type Inner = Self;
+    fn sort() -> ast::Sort {
+        ast::Sort::Sort(Identifier::Simple(Symbol("String".into())))
+    }
+}
+/// Constructs the string literal of the value, escaping non-ASCII characters
+/// as described in [`escape_string`].
+///
+/// # Panics
+///
+/// Panics if the value contains characters above `U+2FFFF`, which are not
+/// part of the theory of strings. Use [`Str::try_from_str`] for strings which
+/// may contain them.
+impl From<&str> for Str {
+    fn from(s: &str) -> Self {
+        Term::SpecConstant(ast::SpecConstant::String(escape_string(s))).into()
+    }
+}
+impl From<String> for Str {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+/// Converts a string literal, such as those found in models, to its value,
+/// decoding escape sequences as described in [`unescape_string`].
+impl TryFrom<Str> for String {
+    type Error = Error;
+
+    fn try_from(value: Str) -> Result<Self, Self::Error> {
+        match &*value.0 {
+            Term::SpecConstant(ast::SpecConstant::String(s)) => unescape_string(s),
+            _ => None,
+        }
+        .ok_or_else(|| Error::UnexpectedTerm {
+            expected: "a string literal",
+            term: value.to_string(),
+        })
+    }
+}
+impl Str {
+    /// Constructs the string literal of `value` like the [`From`] conversion,
+    /// but returns an error rather than panicking if `value` contains
+    /// characters above `U+2FFFF`.
+    pub fn try_from_str(value: &str) -> Result<Str, Error> {
+        match try_escape_string(value) {
+            Some(literal) => Ok(Term::SpecConstant(ast::SpecConstant::String(literal)).into()),
+            None => Err(Error::InvalidString {
+                value: value.to_string(),
+            }),
+        }
+    }
+    fn binop<T: From<Term>>(&self, op: &str, other: Str) -> T {
+        fun(op, vec![self.clone().into(), other.into()]).into()
+    }
+    /// Construct the term expressing `(str.len self)`, that is, the number of
+    /// characters in `self`.
+    pub fn len(&self) -> Int {
+        fun("str.len", vec![self.clone().into()]).into()
+    }
+    /// Construct the term expressing `(= self "")`
+    pub fn is_empty(&self) -> Bool {
+        self.binop("=", Str::from(""))
+    }
+    /// Construct the term expressing `(str.++ self other)`, that is, the
+    /// concatenation of `self` and `other`.
+    pub fn concat(&self, other: impl Into<Self>) -> Str {
+        self.binop("str.++", other.into())
+    }
+    /// Construct the term expressing `(str.contains self other)`, that is,
+    /// whether `other` is a substring of `self`.
+    pub fn contains(&self, other: impl Into<Self>) -> Bool {
+        self.binop("str.contains", other.into())
+    }
+    /// Construct the term expressing `(str.prefixof self other)`, that is,
+    /// whether `self` is a prefix of `other`.
+    pub fn prefix_of(&self, other: impl Into<Self>) -> Bool {
+        self.binop("str.prefixof", other.into())
+    }
+    /// Construct the term expressing `(str.suffixof self other)`, that is,
+    /// whether `self` is a suffix of `other`.
+    pub fn suffix_of(&self, other: impl Into<Self>) -> Bool {
+        self.binop("str.suffixof", other.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{terms::Sort, Error, Int, SatResult, Solver};
+
+    use super::Str;
+
+    #[test]
+    fn unicode_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let s = Str::from_name("s");
+        solver.assert(s._eq("café"))?;
+        assert!(solver.transcript().contains(r#""caf\u{e9}""#));
+
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(String::try_from(model.eval(s).unwrap())?, "café");
+
+        Ok(())
+    }
+
+    #[test]
+    fn length_counts_characters() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let s = Str::from_name("s");
+        solver.assert(s._eq(Str::from("naïve ").concat("\"ünïcödé\"")))?;
+        solver.assert(s.len()._neq(Int::from(15)))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+
+        Ok(())
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(Str::from("say \"hi\"").to_string(), r#""say ""hi""""#);
+        assert_eq!(Str::from(r"a\u{61}").to_string(), r#""a\u{5c}u{61}""#);
+        assert_eq!(String::try_from(Str::from("日本 🦀")).unwrap(), "日本 🦀");
+        assert!(String::try_from(Str::from(Str::from_name("s"))).is_err());
+        assert_eq!(Str::try_from_str("🦀").unwrap(), Str::from("🦀"));
+        assert!(matches!(
+            Str::try_from_str("\u{30000}"),
+            Err(Error::InvalidString { .. })
+        ));
+    }
+}
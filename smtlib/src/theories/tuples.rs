@@ -0,0 +1,217 @@
+//! Tuples of values of possibly different sorts, built on top of
+//! [datatypes](super::datatypes).
+//!
+//! The tuple sorts are parametric datatypes, which the [`Solver`](crate::Solver)
+//! declares the first time they are used, such that tuples can be used like
+//! any other sort.
+//!
+//! ```
+//! # use smtlib::{backend::Z3Binary, tuple, Bool, Int, SatResult, Solver, Sort, Tuple2};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+//! let p = Tuple2::<Int, Bool>::from_name("p");
+//! solver.assert(p._eq(tuple(5, true)))?;
+//! solver.assert(p.first()._neq(5))?;
+//! assert_eq!(solver.check_sat()?, SatResult::Unsat);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{marker::PhantomData, sync::Arc};
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier, Term},
+    lexicon::{Numeral, Symbol},
+};
+
+use crate::terms::{fun, qual_ident, Const, Dynamic, Sort};
+
+macro_rules! impl_tuple {
+    ($ty:ident, $cons:literal, $($field:ident: $t:ident),+) => {
+        #[doc = concat!(
+            "A [`", stringify!($ty), "`] is a term containing a tuple with a field of each of the \
+             sorts `", stringify!($($t),+), "`."
+        )]
+        #[derive(Debug)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(into = "Term", from = "Term")
+        )]
+        pub struct $ty<$($t),+>(Arc<Term>, PhantomData<($($t,)+)>);
+
+        impl<$($t),+> Clone for $ty<$($t),+> {
+            fn clone(&self) -> Self {
+                $ty(self.0.clone(), PhantomData)
+            }
+        }
+        impl<$($t),+> PartialEq for $ty<$($t),+> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl<$($t),+> Eq for $ty<$($t),+> {}
+        impl<$($t),+> std::hash::Hash for $ty<$($t),+> {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        impl<$($t),+> From<Const<$ty<$($t),+>>> for $ty<$($t),+> {
+            fn from(c: Const<$ty<$($t),+>>) -> Self {
+                c.1
+            }
+        }
+        impl<$($t),+> From<&Const<$ty<$($t),+>>> for $ty<$($t),+> {
+            fn from(c: &Const<$ty<$($t),+>>) -> Self {
+                c.1.clone()
+            }
+        }
+        impl<$($t),+> std::fmt::Display for $ty<$($t),+> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl<$($t: Sort),+> From<$ty<$($t),+>> for Dynamic {
+            fn from(t: $ty<$($t),+>) -> Self {
+                Dynamic::with_sort(t, $ty::<$($t),+>::sort())
+            }
+        }
+
+        impl<$($t),+> From<$ty<$($t),+>> for Term {
+            fn from(t: $ty<$($t),+>) -> Self {
+                Arc::unwrap_or_clone(t.0)
+            }
+        }
+        impl<$($t),+> From<Term> for $ty<$($t),+> {
+            fn from(t: Term) -> Self {
+                $ty(Arc::new(t), PhantomData)
+            }
+        }
+        impl<$($t: Sort),+> Sort for $ty<$($t),+> {
+            type Inner = Self;
+            fn sort() -> ast::Sort {
+                ast::Sort::Parametric(
+                    Identifier::Simple(Symbol(stringify!($ty).into())),
+                    vec![$($t::sort()),+],
+                )
+            }
+        }
+        impl<$($t: Sort + From<Term>),+> $ty<$($t),+> {
+            #[doc = concat!(
+                "Construct the term expressing `(", $cons, " ", stringify!($($field)+), ")`, \
+                 that is, the tuple of the given values."
+            )]
+            pub fn new($($field: impl Into<$t>),+) -> Self {
+                Term::Application(
+                    qual_ident($cons.into(), Some(Self::sort())),
+                    vec![$($field.into().into()),+],
+                )
+                .into()
+            }
+            $(
+                #[doc = concat!(
+                    "Construct the term expressing `(", $cons, "_", stringify!($field), " self)`, \
+                     that is, the ", stringify!($field), " field of the tuple."
+                )]
+                pub fn $field(&self) -> $t {
+                    fun(concat!($cons, "_", stringify!($field)), vec![self.clone().into()]).into()
+                }
+            )+
+        }
+    };
+}
+
+impl_tuple!(Tuple2, "tuple2", first: A, second: B);
+impl_tuple!(Tuple3, "tuple3", first: A, second: B, third: C);
+
+/// Construct the term expressing `(tuple2 a b)`, that is, the pair of `a` and
+/// `b`. This is a shorthand for [`Tuple2::new`].
+pub fn tuple<A, B>(a: impl Into<A>, b: impl Into<B>) -> Tuple2<A, B>
+where
+    A: Sort + From<Term>,
+    B: Sort + From<Term>,
+{
+    Tuple2::new(a, b)
+}
+
+/// The command declaring the tuple datatype named `name`, or `None` if `name`
+/// is not one of the tuple sorts.
+pub(crate) fn declaration(name: &str) -> Option<ast::Command> {
+    let fields: &[&str] = match name {
+        "Tuple2" => &["first", "second"],
+        "Tuple3" => &["first", "second", "third"],
+        _ => return None,
+    };
+    let cons = name.to_lowercase();
+    let params: Vec<_> = ["A", "B", "C"][..fields.len()]
+        .iter()
+        .map(|p| Symbol(p.to_string()))
+        .collect();
+    let selectors = fields
+        .iter()
+        .zip(&params)
+        .map(|(field, param)| {
+            ast::SelectorDec(
+                Symbol(format!("{cons}_{field}")),
+                ast::Sort::Sort(Identifier::Simple(param.clone())),
+            )
+        })
+        .collect();
+    Some(ast::Command::DeclareDatatypes(
+        vec![ast::SortDec(
+            Symbol(name.to_string()),
+            Numeral(fields.len().to_string()),
+        )],
+        vec![ast::DatatypeDec::Par(
+            params,
+            vec![ast::ConstructorDec(Symbol(cons), selectors)],
+        )],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{terms::Sort, Bool, Int, Real, SatResult, Solver};
+
+    use super::{tuple, Tuple2, Tuple3};
+
+    #[test]
+    fn pair() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let p = Tuple2::<Int, Bool>::from_name("p");
+        solver.assert(p.first()._eq(5))?;
+        solver.assert(p.second())?;
+        let model = solver.check_sat_with_model()?.expect_sat()?;
+        assert_eq!(model.eval(p).unwrap().to_string(), "(tuple2 5 true)");
+
+        solver.assert(tuple::<Int, Bool>(3, false).first()._eq(4))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+        // The datatype is only declared once
+        assert_eq!(solver.transcript().matches("(declare-datatypes").count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn triple() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+
+        let t = Tuple3::<Int, Real, Tuple2<Int, Int>>::from_name("t");
+        solver.assert(t.third().second()._eq(t.first()))?;
+        solver.assert(t.first().gt(3))?;
+        solver.assert(t.third()._eq(tuple(1, 2)))?;
+        assert_eq!(solver.check_sat()?, SatResult::Unsat);
+        assert_eq!(
+            Tuple3::<Int, Real, Tuple2<Int, Int>>::sort().to_string(),
+            "(Tuple3 Int Real (Tuple2 Int Int))"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Uninterpreted sorts, which are opaque sorts with no other structure than
+//! equality among their values.
+//!
+//! An [`UninterpretedSort`] is obtained by declaring it using
+//! [`Solver::declare_sort`](crate::Solver::declare_sort), after which
+//! constants of the sort are constructed using
+//! [`UninterpretedSort::from_name`].
+
+use smtlib_lowlevel::{
+    ast::{self, Identifier},
+    lexicon::{Numeral, Symbol},
+};
+
+use crate::terms::{Const, Dynamic};
+
+/// A sort declared using `(declare-sort name arity)`. If the arity is
+/// non-zero, the sort is a sort constructor, which must be applied to that
+/// many sorts using [`UninterpretedSort::apply`] to form a sort.
+///
+/// ```
+/// # use smtlib::{backend::Z3Binary, terms::Sort, SatResult, Solver};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+/// let s = solver.declare_sort("S", 0)?;
+/// let (a, b) = (s.from_name("a"), s.from_name("b"));
+/// solver.assert(a._neq(b))?;
+/// assert_eq!(solver.check_sat()?, SatResult::Sat);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UninterpretedSort {
+    name: String,
+    arity: usize,
+}
+
+impl UninterpretedSort {
+    pub(crate) fn new(name: impl Into<String>, arity: usize) -> Self {
+        UninterpretedSort {
+            name: name.into(),
+            arity,
+        }
+    }
+    /// The name of the sort
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The number of sorts the sort must be applied to
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+    /// The sort itself, such as `S`.
+    ///
+    /// Panics if the arity of the sort is non-zero, in which case
+    /// [`UninterpretedSort::apply`] must be used instead.
+    pub fn sort(&self) -> ast::Sort {
+        self.apply([])
+    }
+    /// The sort constructor applied to `args`, such as `(S Int Bool)`.
+    ///
+    /// Panics if the number of `args` differs from the arity of the sort.
+    pub fn apply(&self, args: impl IntoIterator<Item = ast::Sort>) -> ast::Sort {
+        let args: Vec<_> = args.into_iter().collect();
+        assert_eq!(
+            args.len(),
+            self.arity,
+            "{} must be applied to {} sorts",
+            self.name,
+            self.arity
+        );
+        let ident = Identifier::Simple(Symbol(self.name.clone()));
+        if args.is_empty() {
+            ast::Sort::Sort(ident)
+        } else {
+            ast::Sort::Parametric(ident, args)
+        }
+    }
+    /// Construct a constant of the sort. See the documentation of [`Const`]
+    /// for more information about constants.
+    ///
+    /// Panics if the arity of the sort is non-zero, as constants must have a
+    /// sort, and not a sort constructor. Use [`UninterpretedSort::apply`] and
+    /// [`Dynamic::from_name_with_sort`] for those.
+    pub fn from_name(&self, name: impl Into<String>) -> Const<Dynamic> {
+        Dynamic::from_name_with_sort(name, self.sort())
+    }
+    /// The command declaring the sort
+    pub(crate) fn declaration(&self) -> ast::Command {
+        ast::Command::DeclareSort(Symbol(self.name.clone()), Numeral(self.arity.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smtlib_lowlevel::backend::Z3Binary;
+
+    use crate::{distinct, terms::Sort, SatResult, Solver};
+
+    #[test]
+    fn three_distinct_constants() -> Result<(), Box<dyn std::error::Error>> {
+        let mut solver = Solver::new(Z3Binary::new("z3")?, false)?;
+        solver.record_transcript(true);
+
+        let s = solver.declare_sort("S", 0)?;
+        let [a, b, c] = ["a", "b", "c"].map(|name| s.from_name(name));
+        solver.assert(distinct([a, b, c.clone()]))?;
+        assert_eq!(c._eq(s.from_name("d")).to_string(), "(= (as c S) (as d S))");
+        assert_eq!(solver.check_sat()?, SatResult::Sat);
+        assert!(solver.transcript().contains("(declare-sort S 0)"));
+
+        Ok(())
+    }
+}